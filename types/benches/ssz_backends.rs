@@ -0,0 +1,57 @@
+//! Compares the `ssz_new` encoder with the legacy `eth2_ssz` one on a genesis `BeaconState`.
+//!
+//! Run with `cargo bench -p types --features ssz_new`.
+
+use criterion::Criterion;
+use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use ssz::Encode;
+use types::beacon_state::BeaconState;
+use types::config::{Config, MainnetConfig, MinimalConfig};
+use types::test_utils::TestingBeaconStateBuilder;
+
+fn build_state<C: Config>(validator_count: usize) -> BeaconState<C> {
+    let (state, _keypairs) =
+        TestingBeaconStateBuilder::<C>::from_deterministic_keypairs(validator_count).build();
+
+    assert_eq!(state.validators.len(), validator_count);
+    assert_eq!(
+        ssz_new::Serialize::serialize(&state).expect("state should serialize"),
+        state.as_ssz_bytes()
+    );
+
+    state
+}
+
+fn bench_suite<C: Config + 'static>(c: &mut Criterion, spec_desc: &str, validator_count: usize) {
+    let state = build_state::<C>(validator_count);
+    let legacy_state = state.clone();
+    let buffer_state = state.clone();
+
+    c.bench(
+        &format!("{}/{}_validators", spec_desc, validator_count),
+        Benchmark::new("eth2_ssz", move |b| {
+            b.iter(|| black_box(legacy_state.as_ssz_bytes()))
+        })
+        .with_function("ssz_new", move |b| {
+            b.iter(|| black_box(ssz_new::Serialize::serialize(&state)))
+        })
+        .with_function("ssz_new_reused_buffer", move |b| {
+            let mut buffer = Vec::with_capacity(ssz_new::Serialize::serialized_len(&buffer_state));
+            b.iter(|| {
+                buffer.clear();
+                ssz_new::Serialize::serialize_into(&buffer_state, &mut buffer)
+                    .expect("state should serialize");
+                black_box(buffer.len())
+            })
+        })
+        .sample_size(10),
+    );
+}
+
+fn all_benches(c: &mut Criterion) {
+    bench_suite::<MinimalConfig>(c, "minimal", 16_384);
+    bench_suite::<MainnetConfig>(c, "mainnet", 16_384);
+}
+
+criterion_group!(benches, all_benches);
+criterion_main!(benches);
@@ -1,11 +1,22 @@
+use core::cmp;
+
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
+#[cfg(feature = "ssz_new")]
+use ssz_new::{SszDeserialize, SszSerialize, SszType, SszView};
 use ssz_types::{BitVector, FixedVector, VariableList};
 use tree_hash_derive::TreeHash;
+use typenum::Unsigned;
 
-use crate::{config::*, consts, primitives::*, types::*};
+use crate::committees::{self, CommitteeCache, CommitteeCaches};
+use crate::{config::*, consts, misc, primitives::*, types::*};
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct BeaconState<C: Config> {
     pub genesis_time: u64,
     pub slot: Slot,
@@ -24,7 +35,7 @@ pub struct BeaconState<C: Config> {
 
     // Registry
     pub validators: VariableList<Validator, C::ValidatorRegistryLimit>,
-    pub balances: VariableList<u64, C::ValidatorRegistryLimit>,
+    pub balances: VariableList<Gwei, C::ValidatorRegistryLimit>,
 
     // Shuffling
     pub start_shard: u64,
@@ -33,7 +44,7 @@ pub struct BeaconState<C: Config> {
     pub compact_committees_roots: FixedVector<H256, C::EpochsPerHistoricalVector>,
 
     // Slashings
-    pub slashings: FixedVector<u64, C::EpochsPerSlashingsVector>,
+    pub slashings: FixedVector<Gwei, C::EpochsPerSlashingsVector>,
 
     // Attestations
     pub previous_epoch_attestations:
@@ -49,4 +60,421 @@ pub struct BeaconState<C: Config> {
     pub previous_justified_checkpoint: Checkpoint,
     pub current_justified_checkpoint: Checkpoint,
     pub finalized_checkpoint: Checkpoint,
+
+    // Caches
+    #[serde(skip)]
+    #[ssz(skip_serializing)]
+    #[ssz(skip_deserializing)]
+    #[tree_hash(skip_hashing)]
+    pub committee_caches: CommitteeCaches,
+}
+
+impl<C: Config> Default for BeaconState<C> {
+    fn default() -> Self {
+        Self {
+            genesis_time: 0,
+            slot: Slot::new(0),
+            fork: Fork::default(),
+
+            latest_block_header: BeaconBlockHeader::default(),
+            block_roots: FixedVector::from(vec![]),
+            state_roots: FixedVector::from(vec![]),
+            historical_roots: VariableList::default(),
+
+            eth1_data: Eth1Data::default(),
+            eth1_data_votes: VariableList::default(),
+            eth1_deposit_index: 0,
+
+            validators: VariableList::default(),
+            balances: VariableList::default(),
+
+            start_shard: 0,
+            randao_mixes: FixedVector::from(vec![]),
+            active_index_roots: FixedVector::from(vec![]),
+            compact_committees_roots: FixedVector::from(vec![]),
+
+            slashings: FixedVector::from(vec![]),
+
+            previous_epoch_attestations: VariableList::default(),
+            current_epoch_attestations: VariableList::default(),
+
+            previous_crosslinks: FixedVector::from(vec![]),
+            current_crosslinks: FixedVector::from(vec![]),
+
+            justification_bits: BitVector::default(),
+            previous_justified_checkpoint: Checkpoint::default(),
+            current_justified_checkpoint: Checkpoint::default(),
+            finalized_checkpoint: Checkpoint::default(),
+
+            committee_caches: CommitteeCaches::default(),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    BitfieldLengthMismatch { expected: usize, found: usize },
+    CommitteeIndexOutOfBounds { index: u64, count: u64 },
+    EmptyCommittee,
+    EpochOutOfRange { epoch: Epoch, current_epoch: Epoch },
+    ShuffleIndexOutOfBounds { index: u64, index_count: u64 },
+    SlotOutOfRange { slot: Slot, state_slot: Slot },
+    ValidatorIndexOutOfBounds(ValidatorIndex),
+}
+
+impl<C: Config> BeaconState<C> {
+    pub fn get_current_epoch(&self) -> Epoch {
+        self.slot.epoch::<C>()
+    }
+
+    pub fn get_previous_epoch(&self) -> Epoch {
+        let current_epoch = self.get_current_epoch();
+        if current_epoch == C::genesis_epoch() {
+            current_epoch
+        } else {
+            current_epoch - 1
+        }
+    }
+
+    /// Returns the root of the first block of `epoch`. Fails if that slot is not in
+    /// `block_roots`.
+    pub fn get_block_root(&self, epoch: Epoch) -> Result<H256, Error> {
+        self.get_block_root_at_slot(epoch.start_slot::<C>())
+    }
+
+    /// Fails unless `slot` is one of the last `SlotsPerHistoricalRoot` slots before the state's
+    /// slot.
+    pub fn get_block_root_at_slot(&self, slot: Slot) -> Result<H256, Error> {
+        if slot >= self.slot || self.slot > slot + C::SlotsPerHistoricalRoot::to_u64() {
+            return Err(Error::SlotOutOfRange {
+                slot,
+                state_slot: self.slot,
+            });
+        }
+
+        Ok(self.block_roots[(slot % C::SlotsPerHistoricalRoot::to_u64()).as_usize()])
+    }
+
+    /// Fails unless `epoch` is one of the last `EpochsPerHistoricalVector` epochs up to and
+    /// including the current one.
+    pub fn get_randao_mix(&self, epoch: Epoch) -> Result<H256, Error> {
+        let current_epoch = self.get_current_epoch();
+        let epochs_per_historical_vector = C::EpochsPerHistoricalVector::to_u64();
+        if epoch > current_epoch || current_epoch >= epoch + epochs_per_historical_vector {
+            return Err(Error::EpochOutOfRange {
+                epoch,
+                current_epoch,
+            });
+        }
+
+        Ok(self.randao_mixes[(epoch % epochs_per_historical_vector).as_usize()])
+    }
+
+    pub fn get_active_validator_indices(&self, epoch: Epoch) -> Vec<ValidatorIndex> {
+        self.validators
+            .iter()
+            .enumerate()
+            .filter(|(_, validator)| validator.is_active_validator(epoch))
+            .map(|(index, _)| ValidatorIndex::new(index as u64))
+            .collect()
+    }
+
+    pub fn get_validator_churn_limit(&self) -> u64 {
+        let active_validator_count =
+            self.get_active_validator_indices(self.get_current_epoch()).len() as u64;
+
+        cmp::max(
+            C::min_per_epoch_churn_limit(),
+            active_validator_count / C::churn_limit_quotient(),
+        )
+    }
+
+    /// Sums the effective balances of `indices`, returning at least 1 to avoid divisions by
+    /// zero.
+    pub fn get_total_balance<'a>(
+        &self,
+        indices: impl IntoIterator<Item = &'a ValidatorIndex>,
+    ) -> Result<Gwei, Error> {
+        let mut total = Gwei::new(0);
+        for index in indices {
+            total += self.validator(*index)?.effective_balance;
+        }
+        Ok(cmp::max(Gwei::new(1), total))
+    }
+
+    pub fn get_total_active_balance(&self) -> Result<Gwei, Error> {
+        self.get_total_balance(&self.get_active_validator_indices(self.get_current_epoch()))
+    }
+
+    /// Computes and stores the committees of `epoch` unless they are already cached, and drops
+    /// caches of epochs before the previous one.
+    ///
+    /// A cache stays valid for as long as the state is advanced through the state transition.
+    /// Code that edits validators or randomness directly must call `drop_committee_caches`.
+    pub fn build_committee_cache(&mut self, epoch: Epoch) -> Result<(), Error> {
+        self.committee_caches.retain_from(self.get_previous_epoch());
+        if self.committee_caches.get(epoch).is_none() {
+            let cache = CommitteeCache::new(self, epoch)?;
+            self.committee_caches.insert(cache);
+        }
+        Ok(())
+    }
+
+    pub fn committee_cache(&self, epoch: Epoch) -> Option<&CommitteeCache> {
+        self.committee_caches.get(epoch)
+    }
+
+    pub fn drop_committee_caches(&mut self) {
+        self.committee_caches.clear();
+    }
+
+    pub fn validator(&self, index: ValidatorIndex) -> Result<&Validator, Error> {
+        self.validators
+            .get(index.as_usize())
+            .ok_or(Error::ValidatorIndexOutOfBounds(index))
+    }
+
+    pub(crate) fn validator_mut(&mut self, index: ValidatorIndex) -> Result<&mut Validator, Error> {
+        self.validators
+            .get_mut(index.as_usize())
+            .ok_or(Error::ValidatorIndexOutOfBounds(index))
+    }
+
+    pub(crate) fn increase_balance(
+        &mut self,
+        index: ValidatorIndex,
+        delta: Gwei,
+    ) -> Result<(), Error> {
+        let balance = self
+            .balances
+            .get_mut(index.as_usize())
+            .ok_or(Error::ValidatorIndexOutOfBounds(index))?;
+        *balance += delta;
+        Ok(())
+    }
+
+    pub(crate) fn decrease_balance(
+        &mut self,
+        index: ValidatorIndex,
+        delta: Gwei,
+    ) -> Result<(), Error> {
+        let balance = self
+            .balances
+            .get_mut(index.as_usize())
+            .ok_or(Error::ValidatorIndexOutOfBounds(index))?;
+        *balance = balance.saturating_sub(delta);
+        Ok(())
+    }
+
+    pub(crate) fn initiate_validator_exit(&mut self, index: ValidatorIndex) -> Result<(), Error> {
+        if self.validator(index)?.exit_epoch != consts::FAR_FUTURE_EPOCH {
+            return Ok(());
+        }
+
+        let activation_exit_epoch =
+            misc::compute_activation_exit_epoch::<C>(self.get_current_epoch());
+        let mut exit_queue_epoch = self
+            .validators
+            .iter()
+            .map(|validator| validator.exit_epoch)
+            .filter(|exit_epoch| *exit_epoch != consts::FAR_FUTURE_EPOCH)
+            .fold(activation_exit_epoch, cmp::max);
+        let exit_queue_churn = self
+            .validators
+            .iter()
+            .filter(|validator| validator.exit_epoch == exit_queue_epoch)
+            .count() as u64;
+        if exit_queue_churn >= self.get_validator_churn_limit() {
+            exit_queue_epoch += 1;
+        }
+
+        let validator = self.validator_mut(index)?;
+        validator.exit_epoch = exit_queue_epoch;
+        validator.withdrawable_epoch = exit_queue_epoch + C::min_validator_withdrawability_delay();
+        Ok(())
+    }
+
+    pub(crate) fn slash_validator(
+        &mut self,
+        slashed_index: ValidatorIndex,
+        whistleblower_index: Option<ValidatorIndex>,
+    ) -> Result<(), Error> {
+        let epoch = self.get_current_epoch();
+        self.initiate_validator_exit(slashed_index)?;
+
+        let validator = self.validator_mut(slashed_index)?;
+        validator.slashed = true;
+        validator.withdrawable_epoch = cmp::max(
+            validator.withdrawable_epoch,
+            epoch + C::EpochsPerSlashingsVector::to_u64(),
+        );
+        let effective_balance = validator.effective_balance;
+
+        self.slashings[(epoch % C::EpochsPerSlashingsVector::to_u64()).as_usize()] +=
+            effective_balance;
+        self.decrease_balance(
+            slashed_index,
+            effective_balance / C::min_slashing_penalty_quotient(),
+        )?;
+
+        let proposer_index = committees::get_beacon_proposer_index(self)?;
+        let whistleblower_index = whistleblower_index.unwrap_or(proposer_index);
+        let whistleblower_reward = effective_balance / C::whistleblower_reward_quotient();
+        let proposer_reward = whistleblower_reward / C::proposer_reward_quotient();
+        self.increase_balance(proposer_index, proposer_reward)?;
+        self.increase_balance(whistleblower_index, whistleblower_reward - proposer_reward)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validator(activation_epoch: u64, exit_epoch: u64) -> Validator {
+        Validator {
+            pubkey: PublicKey::default(),
+            withdrawal_credentials: H256::zero(),
+            effective_balance: MainnetConfig::max_effective_balance(),
+            slashed: false,
+            activation_eligibility_epoch: Epoch::new(0),
+            activation_epoch: Epoch::new(activation_epoch),
+            exit_epoch: Epoch::new(exit_epoch),
+            withdrawable_epoch: consts::FAR_FUTURE_EPOCH,
+        }
+    }
+
+    fn state_at_slot(slot: u64) -> BeaconState<MainnetConfig> {
+        BeaconState {
+            slot: Slot::new(slot),
+            ..BeaconState::default()
+        }
+    }
+
+    #[test]
+    fn previous_epoch_saturates_at_genesis() {
+        let slots_per_epoch = <MainnetConfig as Config>::SlotsPerEpoch::to_u64();
+
+        assert_eq!(state_at_slot(0).get_previous_epoch(), Epoch::new(0));
+        assert_eq!(
+            state_at_slot(slots_per_epoch).get_previous_epoch(),
+            Epoch::new(0)
+        );
+        assert_eq!(
+            state_at_slot(3 * slots_per_epoch).get_previous_epoch(),
+            Epoch::new(2)
+        );
+    }
+
+    #[test]
+    fn block_root_at_slot_is_bounds_checked() {
+        let history = <MainnetConfig as Config>::SlotsPerHistoricalRoot::to_u64();
+        let state_slot = Slot::new(history + 2);
+        let mut state = state_at_slot(history + 2);
+        state.block_roots[2] = H256::from([2; 32]);
+
+        assert_eq!(
+            state.get_block_root_at_slot(state_slot - 1),
+            Ok(H256::zero())
+        );
+        assert_eq!(
+            state.get_block_root_at_slot(state_slot),
+            Err(Error::SlotOutOfRange {
+                slot: state_slot,
+                state_slot
+            })
+        );
+        assert_eq!(
+            state.get_block_root_at_slot(Slot::new(1)),
+            Err(Error::SlotOutOfRange {
+                slot: Slot::new(1),
+                state_slot
+            })
+        );
+        assert_eq!(
+            state.get_block_root_at_slot(Slot::new(2)),
+            Ok(H256::from([2; 32]))
+        );
+    }
+
+    #[test]
+    fn randao_mix_is_bounds_checked() {
+        let state = state_at_slot(0);
+
+        assert_eq!(state.get_randao_mix(Epoch::new(0)), Ok(H256::zero()));
+        assert_eq!(
+            state.get_randao_mix(Epoch::new(1)),
+            Err(Error::EpochOutOfRange {
+                epoch: Epoch::new(1),
+                current_epoch: Epoch::new(0)
+            })
+        );
+    }
+
+    #[test]
+    fn active_validators_and_balances() {
+        let mut state = state_at_slot(0);
+        for validator in [validator(0, 10), validator(1, 10), validator(0, 0)] {
+            state.validators.push(validator).expect("list is not full");
+        }
+
+        let indices = [0, 1, 3].map(ValidatorIndex::new);
+        assert_eq!(
+            state.get_active_validator_indices(Epoch::new(0)),
+            &indices[..1]
+        );
+        assert_eq!(
+            state.get_active_validator_indices(Epoch::new(1)),
+            &indices[..2]
+        );
+        assert_eq!(
+            state.get_total_balance(&indices[..2]),
+            Ok(MainnetConfig::max_effective_balance() * 2)
+        );
+        assert_eq!(state.get_total_balance(&[]), Ok(Gwei::new(1)));
+        assert_eq!(
+            state.get_total_balance(&indices[2..]),
+            Err(Error::ValidatorIndexOutOfBounds(indices[2]))
+        );
+        assert_eq!(
+            state.get_validator_churn_limit(),
+            MainnetConfig::min_per_epoch_churn_limit()
+        );
+    }
+
+    #[test]
+    fn slashable_validator() {
+        let mut validator = validator(1, 10);
+        validator.withdrawable_epoch = Epoch::new(20);
+
+        assert!(!validator.is_slashable_validator(Epoch::new(0)));
+        assert!(validator.is_slashable_validator(Epoch::new(15)));
+        assert!(!validator.is_slashable_validator(Epoch::new(20)));
+
+        validator.slashed = true;
+        assert!(!validator.is_slashable_validator(Epoch::new(15)));
+    }
+
+    #[test]
+    fn slashable_attestation_data() {
+        let data = |source: u64, target: u64, root: u8| AttestationData {
+            beacon_block_root: H256::from([root; 32]),
+            source: Checkpoint {
+                epoch: Epoch::new(source),
+                root: H256::zero(),
+            },
+            target: Checkpoint {
+                epoch: Epoch::new(target),
+                root: H256::zero(),
+            },
+            crosslink: Crosslink::default(),
+        };
+
+        // Double vote.
+        assert!(data(0, 2, 0).is_slashable_attestation_data(&data(0, 2, 1)));
+        // Surround vote.
+        assert!(data(0, 3, 0).is_slashable_attestation_data(&data(1, 2, 0)));
+        assert!(!data(1, 2, 0).is_slashable_attestation_data(&data(0, 3, 0)));
+        assert!(!data(0, 2, 0).is_slashable_attestation_data(&data(0, 2, 0)));
+    }
 }
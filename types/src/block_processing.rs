@@ -0,0 +1,939 @@
+use core::cmp;
+use std::convert::TryInto;
+
+use bls::AggregatePublicKey;
+use eth2_hashing::hash;
+use ssz_types::VariableList;
+use tree_hash::{SignedRoot, TreeHash};
+use typenum::Unsigned;
+
+use crate::beacon_state::{self, BeaconState};
+use crate::committees;
+use crate::config::Config;
+use crate::consts;
+use crate::misc;
+use crate::primitives::*;
+use crate::signing;
+use crate::types::*;
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    BeaconState(beacon_state::Error),
+    HeaderInvalid { reason: HeaderInvalid },
+    RandaoSignatureInvalid,
+    Eth1DataVotesFull,
+    DepositCountInvalid { expected: u64, found: u64 },
+    DepositIndexPastCount { index: u64, count: u64 },
+    DuplicateTransfers,
+    ProposerSlashingInvalid { reason: ProposerSlashingInvalid },
+    AttesterSlashingInvalid { reason: AttesterSlashingInvalid },
+    AttestationInvalid { reason: AttestationInvalid },
+    PendingAttestationsFull,
+    DepositInvalid { reason: DepositInvalid },
+    ExitInvalid { reason: ExitInvalid },
+    TransferInvalid { reason: TransferInvalid },
+}
+
+#[derive(PartialEq, Debug)]
+pub enum HeaderInvalid {
+    StateSlotMismatch { state: Slot, block: Slot },
+    ParentBlockRootMismatch { state: H256, block: H256 },
+    ProposerSlashed(ValidatorIndex),
+    BadSignature,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ProposerSlashingInvalid {
+    ProposerUnknown(ValidatorIndex),
+    ProposalEpochMismatch { slot_1: Slot, slot_2: Slot },
+    ProposalsIdentical,
+    ProposerNotSlashable(ValidatorIndex),
+    BadProposal1Signature,
+    BadProposal2Signature,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum AttesterSlashingInvalid {
+    NotSlashable,
+    IndexedAttestation1Invalid(IndexedAttestationInvalid),
+    IndexedAttestation2Invalid(IndexedAttestationInvalid),
+    NoSlashableIndices,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum IndexedAttestationInvalid {
+    CustodyBit1Set,
+    TooManyIndices { max: usize, found: usize },
+    CustodyBitIndicesIntersect,
+    IndicesNotSorted,
+    UnknownValidator(ValidatorIndex),
+    BadSignature,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum AttestationInvalid {
+    BadShard(Shard),
+    BadTargetEpoch {
+        target: Epoch,
+        previous: Epoch,
+        current: Epoch,
+    },
+    BitfieldLengthMismatch {
+        aggregation_bits: usize,
+        custody_bits: usize,
+        committee: usize,
+    },
+    IncludedTooEarly {
+        state_slot: Slot,
+        attestation_slot: Slot,
+    },
+    IncludedTooLate {
+        state_slot: Slot,
+        attestation_slot: Slot,
+    },
+    WrongJustifiedCheckpoint {
+        state: Checkpoint,
+        attestation: Checkpoint,
+    },
+    BadParentCrosslinkRoot,
+    BadParentCrosslinkStartEpoch,
+    BadParentCrosslinkEndEpoch,
+    ShardBlockRootNotZero,
+    CustodyBitfieldNotSubset,
+    BadIndexedAttestation(IndexedAttestationInvalid),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum DepositInvalid {
+    BadMerkleProof,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ExitInvalid {
+    ValidatorUnknown(ValidatorIndex),
+    NotActive(ValidatorIndex),
+    AlreadyExited(ValidatorIndex),
+    FutureEpoch {
+        state: Epoch,
+        exit: Epoch,
+    },
+    TooYoungToExit {
+        current_epoch: Epoch,
+        earliest_exit_epoch: Epoch,
+    },
+    BadSignature,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum TransferInvalid {
+    SenderUnknown(ValidatorIndex),
+    RecipientUnknown(ValidatorIndex),
+    AmountOverflow,
+    InsufficientBalance { balance: Gwei, required: Gwei },
+    StateSlotMismatch { state: Slot, transfer: Slot },
+    SenderNotWithdrawable(ValidatorIndex),
+    WithdrawalCredentialsMismatch,
+    BadSignature,
+    SenderDust(Gwei),
+    RecipientDust(Gwei),
+}
+
+impl From<beacon_state::Error> for Error {
+    fn from(error: beacon_state::Error) -> Self {
+        Error::BeaconState(error)
+    }
+}
+
+macro_rules! impl_from_reason {
+    ($reason: ident) => {
+        impl From<$reason> for Error {
+            fn from(reason: $reason) -> Self {
+                Error::$reason { reason }
+            }
+        }
+    };
+}
+
+impl_from_reason!(HeaderInvalid);
+impl_from_reason!(ProposerSlashingInvalid);
+impl_from_reason!(AttesterSlashingInvalid);
+impl_from_reason!(AttestationInvalid);
+impl_from_reason!(DepositInvalid);
+impl_from_reason!(ExitInvalid);
+impl_from_reason!(TransferInvalid);
+
+pub fn process_block<C: Config>(
+    state: &mut BeaconState<C>,
+    block: &BeaconBlock<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    process_block_header(state, block, validate_signatures)?;
+    process_randao(state, &block.body, validate_signatures)?;
+    process_eth1_data(state, &block.body)?;
+    process_operations(state, &block.body, validate_signatures)
+}
+
+pub fn process_block_header<C: Config>(
+    state: &mut BeaconState<C>,
+    block: &BeaconBlock<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    if block.slot != state.slot {
+        return Err(HeaderInvalid::StateSlotMismatch {
+            state: state.slot,
+            block: block.slot,
+        }
+        .into());
+    }
+
+    let expected_parent_root = H256::from_slice(&state.latest_block_header.signed_root());
+    if block.parent_root != expected_parent_root {
+        return Err(HeaderInvalid::ParentBlockRootMismatch {
+            state: expected_parent_root,
+            block: block.parent_root,
+        }
+        .into());
+    }
+
+    state.latest_block_header = BeaconBlockHeader {
+        slot: block.slot,
+        parent_root: block.parent_root,
+        state_root: H256::zero(),
+        body_root: H256::from_slice(&block.body.tree_hash_root()),
+        signature: Signature::empty_signature(),
+    };
+
+    let proposer_index = committees::get_beacon_proposer_index(state)?;
+    let proposer = state.validator(proposer_index)?;
+    if proposer.slashed {
+        return Err(HeaderInvalid::ProposerSlashed(proposer_index).into());
+    }
+
+    if validate_signatures && !block.verify_signature(&proposer.pubkey, state) {
+        return Err(HeaderInvalid::BadSignature.into());
+    }
+
+    Ok(())
+}
+
+pub fn process_randao<C: Config>(
+    state: &mut BeaconState<C>,
+    body: &BeaconBlockBody<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let epoch = state.get_current_epoch();
+
+    if validate_signatures {
+        let proposer = state.validator(committees::get_beacon_proposer_index(state)?)?;
+        if !body.randao_reveal.verify(
+            &epoch.tree_hash_root(),
+            signing::get_domain(state, C::domain_randao(), None),
+            &proposer.pubkey,
+        ) {
+            return Err(Error::RandaoSignatureInvalid);
+        }
+    }
+
+    let reveal_hash = hash(&body.randao_reveal.as_bytes());
+    let mut mix = state.get_randao_mix(epoch)?;
+    for (mix_byte, reveal_byte) in mix.as_bytes_mut().iter_mut().zip(reveal_hash) {
+        *mix_byte ^= reveal_byte;
+    }
+    state.randao_mixes[(epoch % C::EpochsPerHistoricalVector::to_u64()).as_usize()] = mix;
+
+    Ok(())
+}
+
+pub fn process_eth1_data<C: Config>(
+    state: &mut BeaconState<C>,
+    body: &BeaconBlockBody<C>,
+) -> Result<(), Error> {
+    // The list holds one vote per slot of the voting period and is cleared at the end of every
+    // period, so it is only full in a state that was built inconsistently.
+    state
+        .eth1_data_votes
+        .push(body.eth1_data.clone())
+        .map_err(|_| Error::Eth1DataVotesFull)?;
+
+    let vote_count = state
+        .eth1_data_votes
+        .iter()
+        .filter(|vote| **vote == body.eth1_data)
+        .count() as u64;
+    if vote_count * 2 > C::SlotsPerEth1VotingPeriod::to_u64() {
+        state.eth1_data = body.eth1_data.clone();
+    }
+
+    Ok(())
+}
+
+pub fn process_operations<C: Config>(
+    state: &mut BeaconState<C>,
+    body: &BeaconBlockBody<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let pending_deposits = state
+        .eth1_data
+        .deposit_count
+        .checked_sub(state.eth1_deposit_index)
+        .ok_or(Error::DepositIndexPastCount {
+            index: state.eth1_deposit_index,
+            count: state.eth1_data.deposit_count,
+        })?;
+    let expected_deposits = cmp::min(C::MaxDeposits::to_u64(), pending_deposits);
+    if body.deposits.len() as u64 != expected_deposits {
+        return Err(Error::DepositCountInvalid {
+            expected: expected_deposits,
+            found: body.deposits.len() as u64,
+        });
+    }
+
+    let has_duplicate_transfers = body
+        .transfers
+        .iter()
+        .enumerate()
+        .any(|(i, transfer)| body.transfers[i + 1..].contains(transfer));
+    if has_duplicate_transfers {
+        return Err(Error::DuplicateTransfers);
+    }
+
+    for proposer_slashing in body.proposer_slashings.iter() {
+        process_proposer_slashing(state, proposer_slashing, validate_signatures)?;
+    }
+    for attester_slashing in body.attester_slashings.iter() {
+        process_attester_slashing(state, attester_slashing, validate_signatures)?;
+    }
+    for attestation in body.attestations.iter() {
+        process_attestation(state, attestation, validate_signatures)?;
+    }
+    for deposit in body.deposits.iter() {
+        process_deposit(state, deposit)?;
+    }
+    for voluntary_exit in body.voluntary_exits.iter() {
+        process_voluntary_exit(state, voluntary_exit, validate_signatures)?;
+    }
+    for transfer in body.transfers.iter() {
+        process_transfer(state, transfer, validate_signatures)?;
+    }
+
+    Ok(())
+}
+
+pub fn process_proposer_slashing<C: Config>(
+    state: &mut BeaconState<C>,
+    proposer_slashing: &ProposerSlashing,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let proposer_index = proposer_slashing.proposer_index;
+    let proposer = state
+        .validator(proposer_index)
+        .map_err(|_| ProposerSlashingInvalid::ProposerUnknown(proposer_index))?;
+    let header_1 = &proposer_slashing.header_1;
+    let header_2 = &proposer_slashing.header_2;
+
+    if header_1.slot.epoch::<C>() != header_2.slot.epoch::<C>() {
+        return Err(ProposerSlashingInvalid::ProposalEpochMismatch {
+            slot_1: header_1.slot,
+            slot_2: header_2.slot,
+        }
+        .into());
+    }
+
+    if header_1 == header_2 {
+        return Err(ProposerSlashingInvalid::ProposalsIdentical.into());
+    }
+
+    if !proposer.is_slashable_validator(state.get_current_epoch()) {
+        return Err(ProposerSlashingInvalid::ProposerNotSlashable(proposer_index).into());
+    }
+
+    if validate_signatures {
+        let is_valid_header_signature = |header: &BeaconBlockHeader| {
+            let domain = signing::get_domain(
+                state,
+                C::domain_beacon_proposer(),
+                Some(header.slot.epoch::<C>()),
+            );
+            header
+                .signature
+                .verify(&header.signed_root(), domain, &proposer.pubkey)
+        };
+
+        if !is_valid_header_signature(header_1) {
+            return Err(ProposerSlashingInvalid::BadProposal1Signature.into());
+        }
+        if !is_valid_header_signature(header_2) {
+            return Err(ProposerSlashingInvalid::BadProposal2Signature.into());
+        }
+    }
+
+    state.slash_validator(proposer_index, None)?;
+    Ok(())
+}
+
+pub fn process_attester_slashing<C: Config>(
+    state: &mut BeaconState<C>,
+    attester_slashing: &AttesterSlashing<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let attestation_1 = &attester_slashing.attestation_1;
+    let attestation_2 = &attester_slashing.attestation_2;
+
+    if !attestation_1
+        .data
+        .is_slashable_attestation_data(&attestation_2.data)
+    {
+        return Err(AttesterSlashingInvalid::NotSlashable.into());
+    }
+
+    is_valid_indexed_attestation(state, attestation_1, validate_signatures)
+        .map_err(AttesterSlashingInvalid::IndexedAttestation1Invalid)?;
+    is_valid_indexed_attestation(state, attestation_2, validate_signatures)
+        .map_err(AttesterSlashingInvalid::IndexedAttestation2Invalid)?;
+
+    let attesting_indices_2 = attestation_2
+        .custody_bit_0_indices
+        .iter()
+        .chain(attestation_2.custody_bit_1_indices.iter())
+        .collect::<Vec<_>>();
+    let mut slashable_indices = attestation_1
+        .custody_bit_0_indices
+        .iter()
+        .chain(attestation_1.custody_bit_1_indices.iter())
+        .filter(|index| attesting_indices_2.contains(index))
+        .copied()
+        .collect::<Vec<_>>();
+    slashable_indices.sort_unstable();
+    slashable_indices.dedup();
+
+    let current_epoch = state.get_current_epoch();
+    let mut slashed_any = false;
+    for index in slashable_indices {
+        if state.validator(index)?.is_slashable_validator(current_epoch) {
+            state.slash_validator(index, None)?;
+            slashed_any = true;
+        }
+    }
+
+    if !slashed_any {
+        return Err(AttesterSlashingInvalid::NoSlashableIndices.into());
+    }
+
+    Ok(())
+}
+
+pub fn process_attestation<C: Config>(
+    state: &mut BeaconState<C>,
+    attestation: &Attestation<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let data = &attestation.data;
+    let shard = data.crosslink.shard;
+    if shard >= C::ShardCount::to_u64() {
+        return Err(AttestationInvalid::BadShard(shard).into());
+    }
+
+    let previous_epoch = state.get_previous_epoch();
+    let current_epoch = state.get_current_epoch();
+    if data.target.epoch != previous_epoch && data.target.epoch != current_epoch {
+        return Err(AttestationInvalid::BadTargetEpoch {
+            target: data.target.epoch,
+            previous: previous_epoch,
+            current: current_epoch,
+        }
+        .into());
+    }
+
+    let committee = committees::get_crosslink_committee(state, data.target.epoch, shard)?;
+    if attestation.aggregation_bits.len() != committee.len()
+        || attestation.custody_bits.len() != committee.len()
+    {
+        return Err(AttestationInvalid::BitfieldLengthMismatch {
+            aggregation_bits: attestation.aggregation_bits.len(),
+            custody_bits: attestation.custody_bits.len(),
+            committee: committee.len(),
+        }
+        .into());
+    }
+
+    let attestation_slot = committees::get_attestation_data_slot(state, data)?;
+    if attestation_slot + C::min_attestation_inclusion_delay() > state.slot {
+        return Err(AttestationInvalid::IncludedTooEarly {
+            state_slot: state.slot,
+            attestation_slot,
+        }
+        .into());
+    }
+    if state.slot > attestation_slot + C::SlotsPerEpoch::to_u64() {
+        return Err(AttestationInvalid::IncludedTooLate {
+            state_slot: state.slot,
+            attestation_slot,
+        }
+        .into());
+    }
+
+    let (justified_checkpoint, parent_crosslink) = if data.target.epoch == current_epoch {
+        (
+            &state.current_justified_checkpoint,
+            &state.current_crosslinks[shard as usize],
+        )
+    } else {
+        (
+            &state.previous_justified_checkpoint,
+            &state.previous_crosslinks[shard as usize],
+        )
+    };
+
+    if data.source != *justified_checkpoint {
+        return Err(AttestationInvalid::WrongJustifiedCheckpoint {
+            state: justified_checkpoint.clone(),
+            attestation: data.source.clone(),
+        }
+        .into());
+    }
+
+    if data.crosslink.parent_root != H256::from_slice(&parent_crosslink.tree_hash_root()) {
+        return Err(AttestationInvalid::BadParentCrosslinkRoot.into());
+    }
+    if data.crosslink.start_epoch != parent_crosslink.end_epoch {
+        return Err(AttestationInvalid::BadParentCrosslinkStartEpoch.into());
+    }
+    if data.crosslink.end_epoch
+        != cmp::min(
+            data.target.epoch,
+            parent_crosslink.end_epoch + C::max_epochs_per_crosslink(),
+        )
+    {
+        return Err(AttestationInvalid::BadParentCrosslinkEndEpoch.into());
+    }
+    if !data.crosslink.data_root.is_zero() {
+        return Err(AttestationInvalid::ShardBlockRootNotZero.into());
+    }
+
+    let indexed_attestation = get_indexed_attestation(state, attestation)?;
+    is_valid_indexed_attestation(state, &indexed_attestation, validate_signatures)
+        .map_err(AttestationInvalid::BadIndexedAttestation)?;
+
+    let pending_attestation = PendingAttestation {
+        aggregation_bits: attestation.aggregation_bits.clone(),
+        data: data.clone(),
+        inclusion_delay: state.slot - attestation_slot,
+        proposer_index: committees::get_beacon_proposer_index(state)?,
+    };
+
+    // Both lists hold at most `MaxAttestations` per slot of a single epoch, so they are only full
+    // in a state that was built inconsistently.
+    if data.target.epoch == current_epoch {
+        state.current_epoch_attestations.push(pending_attestation)
+    } else {
+        state.previous_epoch_attestations.push(pending_attestation)
+    }
+    .map_err(|_| Error::PendingAttestationsFull)?;
+
+    Ok(())
+}
+
+pub fn process_deposit<C: Config>(state: &mut BeaconState<C>, deposit: &Deposit) -> Result<(), Error> {
+    if !misc::is_valid_merkle_branch(
+        H256::from_slice(&deposit.data.tree_hash_root()),
+        &deposit.proof,
+        consts::DEPOSIT_CONTRACT_TREE_DEPTH + 1,
+        state.eth1_deposit_index,
+        state.eth1_data.deposit_root,
+    ) {
+        return Err(DepositInvalid::BadMerkleProof.into());
+    }
+
+    state.eth1_deposit_index += 1;
+
+    let pubkey_bytes = &deposit.data.pubkey;
+    let amount = deposit.data.amount;
+    let deposit_pubkey = pubkey_bytes.as_bytes();
+    let existing_index = state
+        .validators
+        .iter()
+        .position(|validator| validator.pubkey.as_bytes() == deposit_pubkey);
+
+    match existing_index {
+        Some(index) => state.increase_balance(ValidatorIndex::new(index as u64), amount)?,
+        None => {
+            let pubkey = match pubkey_bytes.try_into() {
+                Ok(pubkey) => pubkey,
+                Err(_) => return Ok(()),
+            };
+            if !deposit.data.verify_signature::<C>(&pubkey) {
+                return Ok(());
+            }
+
+            let validator = Validator {
+                pubkey,
+                withdrawal_credentials: deposit.data.withdrawal_credentials,
+                activation_eligibility_epoch: consts::FAR_FUTURE_EPOCH,
+                activation_epoch: consts::FAR_FUTURE_EPOCH,
+                exit_epoch: consts::FAR_FUTURE_EPOCH,
+                withdrawable_epoch: consts::FAR_FUTURE_EPOCH,
+                effective_balance: cmp::min(
+                    amount - amount % C::effective_balance_increment(),
+                    C::max_effective_balance(),
+                ),
+                slashed: false,
+            };
+            // Both lists are bounded by `ValidatorRegistryLimit`, which cannot be reached.
+            let _ = state.validators.push(validator);
+            let _ = state.balances.push(amount);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn process_voluntary_exit<C: Config>(
+    state: &mut BeaconState<C>,
+    exit: &VoluntaryExit,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let validator_index = exit.validator_index;
+    let validator = state
+        .validator(validator_index)
+        .map_err(|_| ExitInvalid::ValidatorUnknown(validator_index))?;
+    let current_epoch = state.get_current_epoch();
+
+    if !validator.is_active_validator(current_epoch) {
+        return Err(ExitInvalid::NotActive(validator_index).into());
+    }
+
+    if validator.exit_epoch != consts::FAR_FUTURE_EPOCH {
+        return Err(ExitInvalid::AlreadyExited(validator_index).into());
+    }
+
+    if current_epoch < exit.epoch {
+        return Err(ExitInvalid::FutureEpoch {
+            state: current_epoch,
+            exit: exit.epoch,
+        }
+        .into());
+    }
+
+    let earliest_exit_epoch = validator.activation_epoch + C::persistent_committee_period();
+    if current_epoch < earliest_exit_epoch {
+        return Err(ExitInvalid::TooYoungToExit {
+            current_epoch,
+            earliest_exit_epoch,
+        }
+        .into());
+    }
+
+    if validate_signatures && !exit.verify_signature(&validator.pubkey, state) {
+        return Err(ExitInvalid::BadSignature.into());
+    }
+
+    state.initiate_validator_exit(validator_index)?;
+    Ok(())
+}
+
+pub fn process_transfer<C: Config>(
+    state: &mut BeaconState<C>,
+    transfer: &Transfer,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    let sender = state
+        .validator(transfer.sender)
+        .map_err(|_| TransferInvalid::SenderUnknown(transfer.sender))?;
+    if state.validator(transfer.recipient).is_err() {
+        return Err(TransferInvalid::RecipientUnknown(transfer.recipient).into());
+    }
+
+    let total = transfer
+        .amount
+        .checked_add(transfer.fee)
+        .ok_or(TransferInvalid::AmountOverflow)?;
+    let sender_balance = state.balances[transfer.sender.as_usize()];
+    if sender_balance < total {
+        return Err(TransferInvalid::InsufficientBalance {
+            balance: sender_balance,
+            required: total,
+        }
+        .into());
+    }
+
+    if state.slot != transfer.slot {
+        return Err(TransferInvalid::StateSlotMismatch {
+            state: state.slot,
+            transfer: transfer.slot,
+        }
+        .into());
+    }
+
+    let is_withdrawable = sender.activation_eligibility_epoch == consts::FAR_FUTURE_EPOCH
+        || state.get_current_epoch() >= sender.withdrawable_epoch
+        || total + C::max_effective_balance() <= sender_balance;
+    if !is_withdrawable {
+        return Err(TransferInvalid::SenderNotWithdrawable(transfer.sender).into());
+    }
+
+    let withdrawal_credentials =
+        bls::get_withdrawal_credentials(&transfer.pubkey, C::bls_withdrawal_prefix_byte());
+    if sender.withdrawal_credentials.as_bytes() != withdrawal_credentials.as_slice() {
+        return Err(TransferInvalid::WithdrawalCredentialsMismatch.into());
+    }
+
+    if validate_signatures && !transfer.verify_signature(state) {
+        return Err(TransferInvalid::BadSignature.into());
+    }
+
+    state.decrease_balance(transfer.sender, total)?;
+    state.increase_balance(transfer.recipient, transfer.amount)?;
+    let proposer_index = committees::get_beacon_proposer_index(state)?;
+    state.increase_balance(proposer_index, transfer.fee)?;
+
+    let is_dust = |balance: Gwei| balance > 0 && balance < C::min_deposit_amount();
+    let sender_balance = state.balances[transfer.sender.as_usize()];
+    if is_dust(sender_balance) {
+        return Err(TransferInvalid::SenderDust(sender_balance).into());
+    }
+    let recipient_balance = state.balances[transfer.recipient.as_usize()];
+    if is_dust(recipient_balance) {
+        return Err(TransferInvalid::RecipientDust(recipient_balance).into());
+    }
+
+    Ok(())
+}
+
+fn get_indexed_attestation<C: Config>(
+    state: &BeaconState<C>,
+    attestation: &Attestation<C>,
+) -> Result<IndexedAttestation<C>, Error> {
+    let attesting_indices =
+        committees::get_attesting_indices(state, &attestation.data, &attestation.aggregation_bits)?;
+    let custody_bit_1_indices =
+        committees::get_attesting_indices(state, &attestation.data, &attestation.custody_bits)?;
+    if !custody_bit_1_indices.is_subset(&attesting_indices) {
+        return Err(AttestationInvalid::CustodyBitfieldNotSubset.into());
+    }
+
+    let custody_bit_0_indices = attesting_indices
+        .difference(&custody_bit_1_indices)
+        .copied()
+        .collect::<Vec<_>>();
+
+    Ok(IndexedAttestation {
+        // Both sets are subsets of a single committee, which is bounded by
+        // `MaxValidatorsPerCommittee`.
+        custody_bit_0_indices: VariableList::new(custody_bit_0_indices)
+            .expect("committee size is bounded"),
+        custody_bit_1_indices: VariableList::new(custody_bit_1_indices.into_iter().collect())
+            .expect("committee size is bounded"),
+        data: attestation.data.clone(),
+        signature: attestation.signature.clone(),
+    })
+}
+
+fn is_valid_indexed_attestation<C: Config>(
+    state: &BeaconState<C>,
+    indexed_attestation: &IndexedAttestation<C>,
+    validate_signatures: bool,
+) -> Result<(), IndexedAttestationInvalid> {
+    let bit_0_indices = &indexed_attestation.custody_bit_0_indices;
+    let bit_1_indices = &indexed_attestation.custody_bit_1_indices;
+
+    // Custody bits are not used until phase 1.
+    if !bit_1_indices.is_empty() {
+        return Err(IndexedAttestationInvalid::CustodyBit1Set);
+    }
+
+    let max = C::MaxValidatorsPerCommittee::to_usize();
+    let found = bit_0_indices.len() + bit_1_indices.len();
+    if found > max {
+        return Err(IndexedAttestationInvalid::TooManyIndices { max, found });
+    }
+
+    if bit_0_indices
+        .iter()
+        .any(|index| bit_1_indices.contains(index))
+    {
+        return Err(IndexedAttestationInvalid::CustodyBitIndicesIntersect);
+    }
+
+    let is_sorted = |indices: &[ValidatorIndex]| indices.windows(2).all(|pair| pair[0] <= pair[1]);
+    if !is_sorted(bit_0_indices) || !is_sorted(bit_1_indices) {
+        return Err(IndexedAttestationInvalid::IndicesNotSorted);
+    }
+
+    if validate_signatures {
+        let mut aggregate_pubkey = AggregatePublicKey::new();
+        for index in bit_0_indices.iter() {
+            let validator = state
+                .validator(*index)
+                .map_err(|_| IndexedAttestationInvalid::UnknownValidator(*index))?;
+            aggregate_pubkey.add(&validator.pubkey);
+        }
+
+        if !indexed_attestation.verify_signature(&aggregate_pubkey, state) {
+            return Err(IndexedAttestationInvalid::BadSignature);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinimalConfig;
+    use crate::test_utils::state_with_validators;
+
+    fn header(slot: u64) -> BeaconBlockHeader {
+        BeaconBlockHeader {
+            slot: Slot::new(slot),
+            ..BeaconBlockHeader::default()
+        }
+    }
+
+    #[test]
+    fn eth1_data_is_adopted_by_majority() {
+        let mut state = state_with_validators::<MinimalConfig>(0);
+        let mut body = BeaconBlockBody::<MinimalConfig> {
+            randao_reveal: Signature::empty_signature(),
+            eth1_data: Eth1Data::default(),
+            graffiti: [0; 32],
+            proposer_slashings: VariableList::default(),
+            attester_slashings: VariableList::default(),
+            attestations: VariableList::default(),
+            deposits: VariableList::default(),
+            voluntary_exits: VariableList::default(),
+            transfers: VariableList::default(),
+        };
+        body.eth1_data.deposit_count = 5;
+
+        let majority = <MinimalConfig as Config>::SlotsPerEth1VotingPeriod::to_u64() / 2 + 1;
+        for _ in 0..majority - 1 {
+            process_eth1_data(&mut state, &body).expect("list is not full");
+        }
+        assert_eq!(state.eth1_data, Eth1Data::default());
+
+        process_eth1_data(&mut state, &body).expect("list is not full");
+        assert_eq!(state.eth1_data, body.eth1_data);
+    }
+
+    #[test]
+    fn eth1_data_vote_into_full_list_is_rejected() {
+        let mut state = state_with_validators::<MinimalConfig>(0);
+        let period = <MinimalConfig as Config>::SlotsPerEth1VotingPeriod::to_usize();
+        state.eth1_data_votes = VariableList::from(vec![Eth1Data::default(); period]);
+
+        assert_eq!(
+            process_eth1_data(&mut state, &BeaconBlockBody::default()),
+            Err(Error::Eth1DataVotesFull)
+        );
+    }
+
+    #[test]
+    fn deposit_index_past_deposit_count_is_rejected() {
+        let mut state = state_with_validators::<MinimalConfig>(0);
+        state.eth1_deposit_index = 2;
+        state.eth1_data.deposit_count = 1;
+
+        assert_eq!(
+            process_operations(&mut state, &BeaconBlockBody::default(), false),
+            Err(Error::DepositIndexPastCount { index: 2, count: 1 })
+        );
+    }
+
+    #[test]
+    fn proposer_slashing_rejects_identical_headers() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let slashing = ProposerSlashing {
+            proposer_index: ValidatorIndex::new(0),
+            header_1: header(1),
+            header_2: header(1),
+        };
+
+        assert_eq!(
+            process_proposer_slashing(&mut state, &slashing, false),
+            Err(Error::ProposerSlashingInvalid {
+                reason: ProposerSlashingInvalid::ProposalsIdentical
+            })
+        );
+    }
+
+    #[test]
+    fn proposer_slashing_slashes_proposer() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let mut header_2 = header(1);
+        header_2.state_root = H256::from([1; 32]);
+        let slashing = ProposerSlashing {
+            proposer_index: ValidatorIndex::new(3),
+            header_1: header(1),
+            header_2,
+        };
+
+        process_proposer_slashing(&mut state, &slashing, false).expect("slashing is valid");
+
+        let validator = &state.validators[3];
+        assert!(validator.slashed);
+        assert_ne!(validator.exit_epoch, consts::FAR_FUTURE_EPOCH);
+        assert!(state.balances[3] < MinimalConfig::max_effective_balance());
+    }
+
+    #[test]
+    fn proposer_slashing_rejects_unknown_proposer() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let slashing = ProposerSlashing {
+            proposer_index: ValidatorIndex::new(16),
+            header_1: header(1),
+            header_2: header(2),
+        };
+
+        assert_eq!(
+            process_proposer_slashing(&mut state, &slashing, false),
+            Err(Error::ProposerSlashingInvalid {
+                reason: ProposerSlashingInvalid::ProposerUnknown(ValidatorIndex::new(16))
+            })
+        );
+    }
+
+    #[test]
+    fn voluntary_exit_requires_persistent_committee_period() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let exit = VoluntaryExit {
+            epoch: Epoch::new(0),
+            validator_index: ValidatorIndex::new(0),
+            signature: Signature::empty_signature(),
+        };
+
+        assert_eq!(
+            process_voluntary_exit(&mut state, &exit, false),
+            Err(Error::ExitInvalid {
+                reason: ExitInvalid::TooYoungToExit {
+                    current_epoch: Epoch::new(0),
+                    earliest_exit_epoch: Epoch::new(MinimalConfig::persistent_committee_period()),
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn indexed_attestation_indices_must_be_sorted() {
+        let state = state_with_validators::<MinimalConfig>(16);
+        let attestation = IndexedAttestation::<MinimalConfig> {
+            custody_bit_0_indices: VariableList::new(vec![
+                ValidatorIndex::new(2),
+                ValidatorIndex::new(1),
+            ])
+            .expect("list is not full"),
+            custody_bit_1_indices: VariableList::default(),
+            data: AttestationData {
+                beacon_block_root: H256::zero(),
+                source: Checkpoint::default(),
+                target: Checkpoint::default(),
+                crosslink: Crosslink::default(),
+            },
+            signature: Signature::empty_signature(),
+        };
+
+        assert_eq!(
+            is_valid_indexed_attestation(&state, &attestation, false),
+            Err(IndexedAttestationInvalid::IndicesNotSorted)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use typenum::Unsigned;
+
+use crate::config::Config;
+use crate::primitives::{Epoch, Gwei, Slot};
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    Yaml(String),
+    LengthMismatch {
+        name: &'static str,
+        config: u64,
+        spec: u64,
+    },
+}
+
+impl From<serde_yaml::Error> for Error {
+    fn from(error: serde_yaml::Error) -> Self {
+        Error::Yaml(error.to_string())
+    }
+}
+
+/// Runtime counterpart of `Config`, in the format of the `mainnet.yaml` and `minimal.yaml` presets.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ChainSpec {
+    // Misc
+    pub shard_count: u64,
+    pub target_committee_size: u64,
+    pub max_validators_per_committee: u64,
+    pub min_per_epoch_churn_limit: u64,
+    pub churn_limit_quotient: u64,
+    pub shuffle_round_count: u64,
+    pub min_genesis_active_validator_count: u64,
+    pub min_genesis_time: u64,
+
+    // Gwei values
+    pub min_deposit_amount: Gwei,
+    pub max_effective_balance: Gwei,
+    pub ejection_balance: Gwei,
+    pub effective_balance_increment: Gwei,
+
+    // Initial values
+    pub genesis_slot: Slot,
+    pub genesis_epoch: Epoch,
+    #[serde(rename = "BLS_WITHDRAWAL_PREFIX")]
+    pub bls_withdrawal_prefix_byte: u8,
+
+    // Time parameters
+    pub min_attestation_inclusion_delay: u64,
+    pub slots_per_epoch: u64,
+    pub min_seed_lookahead: u64,
+    pub activation_exit_delay: u64,
+    pub slots_per_eth1_voting_period: u64,
+    pub slots_per_historical_root: u64,
+    pub min_validator_withdrawability_delay: u64,
+    pub persistent_committee_period: u64,
+    pub max_epochs_per_crosslink: u64,
+    pub min_epochs_to_inactivity_penalty: u64,
+
+    // State vector lengths
+    pub epochs_per_historical_vector: u64,
+    pub epochs_per_slashings_vector: u64,
+    pub historical_roots_limit: u64,
+    pub validator_registry_limit: u64,
+
+    // Reward and penalty quotients
+    pub base_reward_factor: u64,
+    pub whistleblower_reward_quotient: u64,
+    pub proposer_reward_quotient: u64,
+    pub inactivity_penalty_quotient: u64,
+    pub min_slashing_penalty_quotient: u64,
+
+    // Max operations per block
+    pub max_proposer_slashings: u64,
+    pub max_attester_slashings: u64,
+    pub max_attestations: u64,
+    pub max_deposits: u64,
+    pub max_voluntary_exits: u64,
+    pub max_transfers: u64,
+
+    // Signature domains
+    pub domain_beacon_proposer: u64,
+    pub domain_randao: u64,
+    pub domain_attestation: u64,
+    pub domain_deposit: u64,
+    pub domain_voluntary_exit: u64,
+    pub domain_transfer: u64,
+}
+
+impl ChainSpec {
+    pub fn from_yaml(yaml: &str) -> Result<Self, Error> {
+        Ok(serde_yaml::from_str(yaml)?)
+    }
+
+    pub fn from_config<C: Config>() -> Self {
+        Self {
+            shard_count: C::ShardCount::to_u64(),
+            target_committee_size: C::target_committee_size(),
+            max_validators_per_committee: C::MaxValidatorsPerCommittee::to_u64(),
+            min_per_epoch_churn_limit: C::min_per_epoch_churn_limit(),
+            churn_limit_quotient: C::churn_limit_quotient(),
+            shuffle_round_count: C::shuffle_round_count(),
+            min_genesis_active_validator_count: C::min_genesis_active_validator_count(),
+            min_genesis_time: C::min_genesis_time(),
+
+            min_deposit_amount: C::min_deposit_amount(),
+            max_effective_balance: C::max_effective_balance(),
+            ejection_balance: C::ejection_balance(),
+            effective_balance_increment: C::effective_balance_increment(),
+
+            genesis_slot: C::genesis_slot(),
+            genesis_epoch: C::genesis_epoch(),
+            bls_withdrawal_prefix_byte: C::bls_withdrawal_prefix_byte(),
+
+            min_attestation_inclusion_delay: C::min_attestation_inclusion_delay(),
+            slots_per_epoch: C::SlotsPerEpoch::to_u64(),
+            min_seed_lookahead: C::min_seed_lookahead(),
+            activation_exit_delay: C::activation_exit_delay(),
+            slots_per_eth1_voting_period: C::SlotsPerEth1VotingPeriod::to_u64(),
+            slots_per_historical_root: C::SlotsPerHistoricalRoot::to_u64(),
+            min_validator_withdrawability_delay: C::min_validator_withdrawability_delay(),
+            persistent_committee_period: C::persistent_committee_period(),
+            max_epochs_per_crosslink: C::max_epochs_per_crosslink(),
+            min_epochs_to_inactivity_penalty: C::min_epochs_to_inactivity_penalty(),
+
+            epochs_per_historical_vector: C::EpochsPerHistoricalVector::to_u64(),
+            epochs_per_slashings_vector: C::EpochsPerSlashingsVector::to_u64(),
+            historical_roots_limit: C::HistoricalRootsLimit::to_u64(),
+            validator_registry_limit: C::ValidatorRegistryLimit::to_u64(),
+
+            base_reward_factor: C::base_reward_factor(),
+            whistleblower_reward_quotient: C::whistleblower_reward_quotient(),
+            proposer_reward_quotient: C::proposer_reward_quotient(),
+            inactivity_penalty_quotient: C::inactivity_penalty_quotient(),
+            min_slashing_penalty_quotient: C::min_slashing_penalty_quotient(),
+
+            max_proposer_slashings: C::MaxProposerSlashings::to_u64(),
+            max_attester_slashings: C::MaxAttesterSlashings::to_u64(),
+            max_attestations: C::MaxAttestations::to_u64(),
+            max_deposits: C::MaxDeposits::to_u64(),
+            max_voluntary_exits: C::MaxVoluntaryExits::to_u64(),
+            max_transfers: C::MaxTransfers::to_u64(),
+
+            domain_beacon_proposer: C::domain_beacon_proposer(),
+            domain_randao: C::domain_randao(),
+            domain_attestation: C::domain_attestation(),
+            domain_deposit: C::domain_deposit(),
+            domain_voluntary_exit: C::domain_voluntary_exit(),
+            domain_transfer: C::domain_transfer(),
+        }
+    }
+
+    /// Checks that the lengths fixed at compile time by `C` agree with this spec.
+    pub fn check_lengths<C: Config>(&self) -> Result<(), Error> {
+        let lengths = [
+            ("SHARD_COUNT", C::ShardCount::to_u64(), self.shard_count),
+            (
+                "MAX_VALIDATORS_PER_COMMITTEE",
+                C::MaxValidatorsPerCommittee::to_u64(),
+                self.max_validators_per_committee,
+            ),
+            (
+                "SLOTS_PER_EPOCH",
+                C::SlotsPerEpoch::to_u64(),
+                self.slots_per_epoch,
+            ),
+            (
+                "SLOTS_PER_ETH1_VOTING_PERIOD",
+                C::SlotsPerEth1VotingPeriod::to_u64(),
+                self.slots_per_eth1_voting_period,
+            ),
+            (
+                "SLOTS_PER_HISTORICAL_ROOT",
+                C::SlotsPerHistoricalRoot::to_u64(),
+                self.slots_per_historical_root,
+            ),
+            (
+                "EPOCHS_PER_HISTORICAL_VECTOR",
+                C::EpochsPerHistoricalVector::to_u64(),
+                self.epochs_per_historical_vector,
+            ),
+            (
+                "EPOCHS_PER_SLASHINGS_VECTOR",
+                C::EpochsPerSlashingsVector::to_u64(),
+                self.epochs_per_slashings_vector,
+            ),
+            (
+                "HISTORICAL_ROOTS_LIMIT",
+                C::HistoricalRootsLimit::to_u64(),
+                self.historical_roots_limit,
+            ),
+            (
+                "VALIDATOR_REGISTRY_LIMIT",
+                C::ValidatorRegistryLimit::to_u64(),
+                self.validator_registry_limit,
+            ),
+            (
+                "MAX_PROPOSER_SLASHINGS",
+                C::MaxProposerSlashings::to_u64(),
+                self.max_proposer_slashings,
+            ),
+            (
+                "MAX_ATTESTER_SLASHINGS",
+                C::MaxAttesterSlashings::to_u64(),
+                self.max_attester_slashings,
+            ),
+            (
+                "MAX_ATTESTATIONS",
+                C::MaxAttestations::to_u64(),
+                self.max_attestations,
+            ),
+            ("MAX_DEPOSITS", C::MaxDeposits::to_u64(), self.max_deposits),
+            (
+                "MAX_VOLUNTARY_EXITS",
+                C::MaxVoluntaryExits::to_u64(),
+                self.max_voluntary_exits,
+            ),
+            (
+                "MAX_TRANSFERS",
+                C::MaxTransfers::to_u64(),
+                self.max_transfers,
+            ),
+            // Not a preset value; the spec derives it as `MAX_ATTESTATIONS * SLOTS_PER_EPOCH`.
+            (
+                "MAX_ATTESTATIONS * SLOTS_PER_EPOCH",
+                C::MaxAttestationsPerEpoch::to_u64(),
+                self.max_attestations * self.slots_per_epoch,
+            ),
+        ];
+
+        for &(name, config, spec) in lengths.iter() {
+            if config != spec {
+                return Err(Error::LengthMismatch { name, config, spec });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MainnetConfig, MinimalConfig};
+
+    const MAINNET_YAML: &str = include_str!("../presets/mainnet.yaml");
+    const MINIMAL_YAML: &str = include_str!("../presets/minimal.yaml");
+
+    #[test]
+    fn minimal_preset_matches_minimal_config() {
+        let spec = ChainSpec::from_yaml(MINIMAL_YAML).expect("preset should parse");
+
+        assert_eq!(spec, ChainSpec::from_config::<MinimalConfig>());
+        assert_eq!(spec.check_lengths::<MinimalConfig>(), Ok(()));
+    }
+
+    #[test]
+    fn mainnet_preset_matches_mainnet_config() {
+        let spec = ChainSpec::from_yaml(MAINNET_YAML).expect("preset should parse");
+
+        assert_eq!(spec, ChainSpec::from_config::<MainnetConfig>());
+        assert_eq!(spec.check_lengths::<MainnetConfig>(), Ok(()));
+    }
+
+    #[test]
+    fn mainnet_preset_does_not_fit_minimal_config() {
+        let spec = ChainSpec::from_yaml(MAINNET_YAML).expect("preset should parse");
+
+        assert_eq!(
+            spec.check_lengths::<MinimalConfig>(),
+            Err(Error::LengthMismatch {
+                name: "SHARD_COUNT",
+                config: 8,
+                spec: 1024,
+            })
+        );
+    }
+
+    #[test]
+    fn config_round_trips_through_yaml() {
+        let spec = ChainSpec::from_config::<MainnetConfig>();
+        let yaml = serde_yaml::to_string(&spec).expect("spec should serialize");
+
+        assert_eq!(ChainSpec::from_yaml(&yaml), Ok(spec));
+    }
+
+    #[test]
+    fn missing_field_is_an_error() {
+        assert!(ChainSpec::from_yaml("SHARD_COUNT: 8").is_err());
+    }
+}
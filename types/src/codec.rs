@@ -0,0 +1,43 @@
+//! Encodes and decodes with the SSZ backend picked by the `ssz_new` feature: `ssz_new` when it is
+//! enabled, the legacy `eth2_ssz` otherwise.
+
+#[cfg(not(feature = "ssz_new"))]
+pub use ssz::{Decode, DecodeError as Error, Encode};
+#[cfg(feature = "ssz_new")]
+pub use ssz_new::{Deserialize as Decode, Error, Serialize as Encode};
+
+#[cfg(not(feature = "ssz_new"))]
+pub fn encode<T: Encode>(value: &T) -> Result<Vec<u8>, Error> {
+    Ok(value.as_ssz_bytes())
+}
+
+#[cfg(feature = "ssz_new")]
+pub fn encode<T: Encode>(value: &T) -> Result<Vec<u8>, Error> {
+    value.serialize()
+}
+
+#[cfg(not(feature = "ssz_new"))]
+pub fn decode<T: Decode>(bytes: &[u8]) -> Result<T, Error> {
+    T::from_ssz_bytes(bytes)
+}
+
+#[cfg(feature = "ssz_new")]
+pub fn decode<T: Decode>(bytes: &[u8]) -> Result<T, Error> {
+    T::deserialize(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::beacon_state::BeaconState;
+    use crate::config::MinimalConfig;
+    use crate::test_utils::state_with_validators;
+
+    #[test]
+    fn state_round_trip() {
+        let state = state_with_validators::<MinimalConfig>(4);
+        let bytes = encode(&state).expect("state should encode");
+        let decoded = decode::<BeaconState<MinimalConfig>>(&bytes).expect("bytes should decode");
+        assert_eq!(decoded, state);
+    }
+}
@@ -0,0 +1,479 @@
+use core::cmp;
+use std::collections::{BTreeMap, BTreeSet};
+
+use eth2_hashing::hash;
+use ssz_types::BitList;
+use typenum::Unsigned;
+
+use crate::beacon_state::{BeaconState, Error};
+use crate::config::Config;
+use crate::misc::{bytes_to_u64, int_to_bytes32};
+use crate::primitives::*;
+use crate::types::AttestationData;
+
+const MAX_RANDOM_BYTE: u64 = 0xff;
+
+/// Shuffled committees of a single epoch.
+///
+/// Shuffling every active validator is the expensive part of committee computation, so
+/// `BeaconState::build_committee_cache` stores one of these per epoch and the functions in this
+/// module read committees from it when it is present.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CommitteeCache {
+    epoch: Epoch,
+    shuffling: Vec<ValidatorIndex>,
+    committee_count: u64,
+    start_shard: Shard,
+}
+
+impl CommitteeCache {
+    pub fn new<C: Config>(state: &BeaconState<C>, epoch: Epoch) -> Result<Self, Error> {
+        let start_shard = get_start_shard(state, epoch)?;
+        let seed = get_seed(state, epoch);
+
+        Ok(Self {
+            epoch,
+            shuffling: shuffle_list::<C>(
+                state.get_active_validator_indices(epoch),
+                seed.as_bytes(),
+            ),
+            committee_count: get_committee_count(state, epoch),
+            start_shard,
+        })
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn committee_count(&self) -> u64 {
+        self.committee_count
+    }
+
+    pub fn start_shard(&self) -> Shard {
+        self.start_shard
+    }
+
+    pub fn shuffling(&self) -> &[ValidatorIndex] {
+        &self.shuffling
+    }
+
+    pub fn crosslink_committee<C: Config>(&self, shard: Shard) -> Result<&[ValidatorIndex], Error> {
+        let shard_count = C::ShardCount::to_u64();
+        let index = (shard + shard_count - self.start_shard) % shard_count;
+        let (start, end) = committee_bounds(self.shuffling.len(), index, self.committee_count)?;
+
+        Ok(&self.shuffling[start..end])
+    }
+}
+
+/// Committee caches held by a `BeaconState`, keyed by epoch.
+///
+/// The caches only hold data derived from the rest of the state, so they never make two states
+/// unequal.
+#[derive(Clone, Default, Debug)]
+pub struct CommitteeCaches(BTreeMap<Epoch, CommitteeCache>);
+
+impl CommitteeCaches {
+    pub fn get(&self, epoch: Epoch) -> Option<&CommitteeCache> {
+        self.0.get(&epoch)
+    }
+
+    pub fn insert(&mut self, cache: CommitteeCache) {
+        self.0.insert(cache.epoch, cache);
+    }
+
+    pub fn retain_from(&mut self, oldest_epoch: Epoch) {
+        self.0 = self.0.split_off(&oldest_epoch);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl PartialEq for CommitteeCaches {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+pub fn compute_shuffled_index<C: Config>(
+    index: u64,
+    index_count: u64,
+    seed: &[u8],
+) -> Result<u64, Error> {
+    if index >= index_count {
+        return Err(Error::ShuffleIndexOutOfBounds { index, index_count });
+    }
+
+    let mut index = index;
+    for current_round in 0..C::shuffle_round_count() {
+        let round_bytes = [current_round as u8];
+
+        let pivot = bytes_to_u64(&hash(&[seed, &round_bytes].concat())[..8]) % index_count;
+        let flip = (pivot + index_count - index) % index_count;
+        let position = cmp::max(index, flip);
+        let source = hash(
+            &[
+                seed,
+                &round_bytes,
+                &((position / 256) as u32).to_le_bytes(),
+            ]
+            .concat(),
+        );
+        let byte = source[((position % 256) / 8) as usize];
+        let bit = (byte >> (position % 8)) % 2;
+
+        if bit == 1 {
+            index = flip;
+        }
+    }
+
+    Ok(index)
+}
+
+/// Shuffles the whole list at once, so that `output[i]` equals
+/// `indices[compute_shuffled_index(i)]`.
+///
+/// This hashes once per 256 positions per round instead of once per position per round.
+pub fn shuffle_list<C: Config>(
+    mut indices: Vec<ValidatorIndex>,
+    seed: &[u8],
+) -> Vec<ValidatorIndex> {
+    let list_size = indices.len();
+    if list_size == 0 {
+        return indices;
+    }
+
+    let source_hash = |round: u8, position: usize| {
+        hash(&[seed, &[round], &(position as u32).to_le_bytes()].concat())
+    };
+    let bit_at =
+        |source: &[u8], position: usize| (source[(position & 0xff) >> 3] >> (position & 0x07)) & 1;
+
+    // The rounds are applied in reverse, which inverts the permutation computed by
+    // `compute_shuffled_index`.
+    for round in (0..C::shuffle_round_count()).rev() {
+        let round = round as u8;
+        let pivot =
+            (bytes_to_u64(&hash(&[seed, &[round]].concat())[..8]) % list_size as u64) as usize;
+
+        // Swap pairs mirrored around `pivot / 2`.
+        let mut source = source_hash(round, pivot >> 8);
+        for i in 0..(pivot + 1) >> 1 {
+            let j = pivot - i;
+            if j & 0xff == 0xff {
+                source = source_hash(round, j >> 8);
+            }
+            if bit_at(&source, j) == 1 {
+                indices.swap(i, j);
+            }
+        }
+
+        // Swap pairs mirrored around `(pivot + list_size) / 2`.
+        let end = list_size - 1;
+        let mut source = source_hash(round, end >> 8);
+        for (offset, i) in ((pivot + 1)..(pivot + list_size + 1) >> 1).enumerate() {
+            let j = end - offset;
+            if j & 0xff == 0xff {
+                source = source_hash(round, j >> 8);
+            }
+            if bit_at(&source, j) == 1 {
+                indices.swap(i, j);
+            }
+        }
+    }
+
+    indices
+}
+
+pub fn compute_committee<C: Config>(
+    indices: &[ValidatorIndex],
+    seed: &[u8],
+    index: u64,
+    count: u64,
+) -> Result<Vec<ValidatorIndex>, Error> {
+    let indices_count = indices.len() as u64;
+    let (start, end) = committee_bounds(indices.len(), index, count)?;
+
+    (start as u64..end as u64)
+        .map(|i| {
+            compute_shuffled_index::<C>(i, indices_count, seed)
+                .map(|shuffled| indices[shuffled as usize])
+        })
+        .collect()
+}
+
+fn committee_bounds(list_size: usize, index: u64, count: u64) -> Result<(usize, usize), Error> {
+    if index >= count {
+        return Err(Error::CommitteeIndexOutOfBounds { index, count });
+    }
+
+    let list_size = list_size as u64;
+    Ok((
+        (list_size * index / count) as usize,
+        (list_size * (index + 1) / count) as usize,
+    ))
+}
+
+pub fn get_seed<C: Config>(state: &BeaconState<C>, epoch: Epoch) -> H256 {
+    let epochs_per_historical_vector = C::EpochsPerHistoricalVector::to_u64();
+    // The mix is read modulo the vector length so that epochs before genesis wrap around.
+    let mix_epoch = epoch + epochs_per_historical_vector - C::min_seed_lookahead() - 1;
+    let mix = state.randao_mixes[(mix_epoch % epochs_per_historical_vector).as_usize()];
+    let active_index_root =
+        state.active_index_roots[(epoch % epochs_per_historical_vector).as_usize()];
+
+    H256::from_slice(&hash(
+        &[
+            mix.as_bytes(),
+            active_index_root.as_bytes(),
+            &int_to_bytes32(epoch.as_u64()),
+        ]
+        .concat(),
+    ))
+}
+
+pub fn get_committee_count<C: Config>(state: &BeaconState<C>, epoch: Epoch) -> u64 {
+    let slots_per_epoch = C::SlotsPerEpoch::to_u64();
+    let active_validators_count = state.get_active_validator_indices(epoch).len() as u64;
+
+    let committees_per_slot = cmp::max(
+        1,
+        cmp::min(
+            C::ShardCount::to_u64() / slots_per_epoch,
+            active_validators_count / slots_per_epoch / C::target_committee_size(),
+        ),
+    );
+
+    committees_per_slot * slots_per_epoch
+}
+
+pub fn get_shard_delta<C: Config>(state: &BeaconState<C>, epoch: Epoch) -> u64 {
+    let shard_count = C::ShardCount::to_u64();
+
+    cmp::min(
+        get_committee_count(state, epoch),
+        shard_count - shard_count / C::SlotsPerEpoch::to_u64(),
+    )
+}
+
+pub fn get_start_shard<C: Config>(state: &BeaconState<C>, epoch: Epoch) -> Result<Shard, Error> {
+    let current_epoch = state.get_current_epoch();
+    if epoch > current_epoch + 1 {
+        return Err(Error::EpochOutOfRange {
+            epoch,
+            current_epoch,
+        });
+    }
+
+    let shard_count = C::ShardCount::to_u64();
+    let mut check_epoch = current_epoch + 1;
+    let mut shard = (state.start_shard + get_shard_delta(state, current_epoch)) % shard_count;
+    while check_epoch > epoch {
+        check_epoch -= 1;
+        shard = (shard + shard_count - get_shard_delta(state, check_epoch)) % shard_count;
+    }
+
+    Ok(shard)
+}
+
+pub fn get_crosslink_committee<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+    shard: Shard,
+) -> Result<Vec<ValidatorIndex>, Error> {
+    if let Some(cache) = state.committee_cache(epoch) {
+        return cache.crosslink_committee::<C>(shard).map(<[_]>::to_vec);
+    }
+
+    let shard_count = C::ShardCount::to_u64();
+    let index = (shard + shard_count - get_start_shard(state, epoch)?) % shard_count;
+
+    compute_committee::<C>(
+        &state.get_active_validator_indices(epoch),
+        get_seed(state, epoch).as_bytes(),
+        index,
+        get_committee_count(state, epoch),
+    )
+}
+
+pub fn get_attestation_data_slot<C: Config>(
+    state: &BeaconState<C>,
+    data: &AttestationData,
+) -> Result<Slot, Error> {
+    let epoch = data.target.epoch;
+    let shard_count = C::ShardCount::to_u64();
+    let committees_per_slot = get_committee_count(state, epoch) / C::SlotsPerEpoch::to_u64();
+    let offset = (data.crosslink.shard + shard_count - get_start_shard(state, epoch)?) % shard_count;
+
+    Ok(epoch.start_slot::<C>() + offset / committees_per_slot)
+}
+
+pub fn get_attesting_indices<C: Config>(
+    state: &BeaconState<C>,
+    data: &AttestationData,
+    bits: &BitList<C::MaxValidatorsPerCommittee>,
+) -> Result<BTreeSet<ValidatorIndex>, Error> {
+    let committee = get_crosslink_committee(state, data.target.epoch, data.crosslink.shard)?;
+    if bits.len() != committee.len() {
+        return Err(Error::BitfieldLengthMismatch {
+            expected: committee.len(),
+            found: bits.len(),
+        });
+    }
+
+    Ok(committee
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| bits.get(*i).unwrap_or(false))
+        .map(|(_, index)| index)
+        .collect())
+}
+
+pub fn get_beacon_proposer_index<C: Config>(
+    state: &BeaconState<C>,
+) -> Result<ValidatorIndex, Error> {
+    let epoch = state.get_current_epoch();
+    let committees_per_slot = get_committee_count(state, epoch) / C::SlotsPerEpoch::to_u64();
+    let offset = committees_per_slot * (state.slot % C::SlotsPerEpoch::to_u64()).as_u64();
+    let shard = (get_start_shard(state, epoch)? + offset) % C::ShardCount::to_u64();
+    let first_committee = get_crosslink_committee(state, epoch, shard)?;
+    if first_committee.is_empty() {
+        return Err(Error::EmptyCommittee);
+    }
+
+    let seed = get_seed(state, epoch);
+    let mut i = 0;
+    loop {
+        let candidate_index = first_committee[((epoch + i) % first_committee.len() as u64).as_usize()];
+        let random_byte =
+            hash(&[seed.as_bytes(), &(i / 32).to_le_bytes()].concat())[(i % 32) as usize];
+        let effective_balance = state.validator(candidate_index)?.effective_balance;
+
+        if effective_balance * MAX_RANDOM_BYTE >= C::max_effective_balance() * u64::from(random_byte)
+        {
+            return Ok(candidate_index);
+        }
+
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MainnetConfig, MinimalConfig};
+    use crate::test_utils::state_with_validators;
+
+    #[test]
+    fn shuffled_index_is_permutation() {
+        let seed = [7; 32];
+        let index_count = 100;
+
+        let mut shuffled = (0..index_count)
+            .map(|index| compute_shuffled_index::<MainnetConfig>(index, index_count, &seed))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("indices are in bounds");
+        shuffled.sort();
+
+        assert_eq!(shuffled, (0..index_count).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn shuffled_index_out_of_bounds() {
+        assert_eq!(
+            compute_shuffled_index::<MainnetConfig>(5, 5, &[0; 32]),
+            Err(Error::ShuffleIndexOutOfBounds {
+                index: 5,
+                index_count: 5
+            })
+        );
+    }
+
+    #[test]
+    fn shuffle_list_matches_shuffled_index() {
+        let seed = [42; 32];
+        for list_size in &[1, 2, 3, 255, 256, 257, 1000] {
+            let list_size = *list_size;
+            let indices = (0..list_size)
+                .map(|i| ValidatorIndex::new(i * 3))
+                .collect::<Vec<_>>();
+
+            let expected = (0..list_size)
+                .map(|i| {
+                    compute_shuffled_index::<MainnetConfig>(i, list_size, &seed)
+                        .map(|shuffled| indices[shuffled as usize])
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .expect("indices are in bounds");
+
+            assert_eq!(shuffle_list::<MainnetConfig>(indices, &seed), expected);
+        }
+    }
+
+    #[test]
+    fn committees_split_indices_evenly() {
+        let indices = (0..10).map(ValidatorIndex::new).collect::<Vec<_>>();
+        let seed = [1; 32];
+
+        let mut all = (0..3)
+            .map(|index| compute_committee::<MainnetConfig>(&indices, &seed, index, 3))
+            .collect::<Result<Vec<_>, _>>()
+            .expect("committees are computable")
+            .concat();
+        all.sort();
+
+        assert_eq!(all, indices);
+        assert_eq!(
+            compute_committee::<MainnetConfig>(&indices, &seed, 3, 3),
+            Err(Error::CommitteeIndexOutOfBounds { index: 3, count: 3 })
+        );
+    }
+
+    #[test]
+    fn cached_committees_match_computed_committees() {
+        let mut state = state_with_validators::<MinimalConfig>(100);
+        let epoch = state.get_current_epoch();
+        let shards = 0..<MinimalConfig as Config>::ShardCount::to_u64();
+
+        let computed = shards
+            .clone()
+            .map(|shard| get_crosslink_committee(&state, epoch, shard))
+            .collect::<Vec<_>>();
+        let proposer = get_beacon_proposer_index(&state);
+
+        state
+            .build_committee_cache(epoch)
+            .expect("cache can be built");
+        assert!(state.committee_cache(epoch).is_some());
+
+        let cached = shards
+            .map(|shard| get_crosslink_committee(&state, epoch, shard))
+            .collect::<Vec<_>>();
+        assert_eq!(cached, computed);
+        assert_eq!(get_beacon_proposer_index(&state), proposer);
+    }
+
+    #[test]
+    fn stale_committee_caches_are_dropped() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let epochs = [0, 1, 2].map(Epoch::new);
+        state
+            .build_committee_cache(epochs[0])
+            .expect("cache can be built");
+        state
+            .build_committee_cache(epochs[1])
+            .expect("cache can be built");
+
+        state.slot = epochs[2].start_slot::<MinimalConfig>();
+        state
+            .build_committee_cache(epochs[2])
+            .expect("cache can be built");
+
+        assert!(state.committee_cache(epochs[0]).is_none());
+        assert!(state.committee_cache(epochs[1]).is_some());
+        assert!(state.committee_cache(epochs[2]).is_some());
+    }
+}
@@ -6,7 +6,7 @@ use core::hash::Hash;
 use serde::{Deserialize, Serialize};
 use typenum::Unsigned;
 
-use crate::primitives::ValidatorIndex;
+use crate::primitives::{Epoch, Gwei, Slot};
 
 pub trait Config
 where
@@ -203,23 +203,23 @@ where
     fn domain_voluntary_exit() -> u64 {
         4
     }
-    fn effective_balance_increment() -> u64 {
-        1_000_000_000
+    fn effective_balance_increment() -> Gwei {
+        Gwei::new(1_000_000_000)
     }
-    fn ejection_balance() -> u64 {
-        16_000_000_000
+    fn ejection_balance() -> Gwei {
+        Gwei::new(16_000_000_000)
     }
-    fn genesis_epoch() -> u64 {
-        0
+    fn genesis_epoch() -> Epoch {
+        Epoch::new(0)
     }
-    fn genesis_slot() -> u64 {
-        0
+    fn genesis_slot() -> Slot {
+        Slot::new(0)
     }
     fn inactivity_penalty_quotient() -> u64 {
         2_u64.pow(25)
     }
-    fn max_effective_balance() -> u64 {
-        32_000_000_000
+    fn max_effective_balance() -> Gwei {
+        Gwei::new(32_000_000_000)
     }
     fn max_epochs_per_crosslink() -> u64 {
         4
@@ -227,8 +227,8 @@ where
     fn min_attestation_inclusion_delay() -> u64 {
         1
     }
-    fn min_deposit_amount() -> u64 {
-        1_000_000_000
+    fn min_deposit_amount() -> Gwei {
+        Gwei::new(1_000_000_000)
     }
     fn min_epochs_to_inactivity_penalty() -> u64 {
         4
@@ -276,22 +276,35 @@ where
 pub struct MainnetConfig {}
 
 impl Config for MainnetConfig {
-    type EpochsPerSlashingsVector = typenum::U64;
-    type EpochsPerHistoricalVector = typenum::U64;
+    type EpochsPerSlashingsVector = typenum::U8192;
+    type EpochsPerHistoricalVector = typenum::U65536;
     type HistoricalRootsLimit = typenum::U16777216;
     type MaxAttesterSlashings = typenum::U1;
     type MaxAttestations = typenum::U128;
-    type MaxAttestationsPerEpoch = typenum::U1024;
+    type MaxAttestationsPerEpoch = typenum::U8192;
     type MaxDeposits = typenum::U16;
     type MaxProposerSlashings = typenum::U16;
     type MaxTransfers = typenum::U0;
     type MaxValidatorsPerCommittee = typenum::U4096;
     type MaxVoluntaryExits = typenum::U16;
-    type ShardCount = typenum::U8;
-    type SlotsPerEpoch = typenum::U8;
-    type SlotsPerEth1VotingPeriod = typenum::U16;
-    type SlotsPerHistoricalRoot = typenum::U64;
+    type ShardCount = typenum::U1024;
+    type SlotsPerEpoch = typenum::U64;
+    type SlotsPerEth1VotingPeriod = typenum::U1024;
+    type SlotsPerHistoricalRoot = typenum::U8192;
     type ValidatorRegistryLimit = typenum::U1099511627776;
+
+    fn max_epochs_per_crosslink() -> u64 {
+        64
+    }
+    fn min_genesis_active_validator_count() -> u64 {
+        65536
+    }
+    fn shuffle_round_count() -> u64 {
+        90
+    }
+    fn target_committee_size() -> u64 {
+        128
+    }
 }
 
 #[derive(
@@ -341,7 +354,7 @@ impl Config for QuickConfig {
     type SlotsPerHistoricalRoot = typenum::U64;
     type ValidatorRegistryLimit = typenum::U1099511627776;
 
-    fn min_genesis_active_validator_count() -> ValidatorIndex {
+    fn min_genesis_active_validator_count() -> u64 {
         1
     }
     fn min_genesis_time() -> u64 {
@@ -0,0 +1,751 @@
+use core::{cmp, convert::TryFrom, mem};
+use std::collections::{BTreeMap, BTreeSet};
+
+use ssz_types::{FixedVector, VariableList};
+use tree_hash::TreeHash;
+use typenum::Unsigned;
+
+use crate::beacon_state::{self, BeaconState};
+use crate::committees;
+use crate::config::Config;
+use crate::consts;
+use crate::misc;
+use crate::primitives::*;
+use crate::types::*;
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    BeaconState(beacon_state::Error),
+    HistoricalRootsFull,
+}
+
+impl From<beacon_state::Error> for Error {
+    fn from(error: beacon_state::Error) -> Self {
+        Error::BeaconState(error)
+    }
+}
+
+pub fn process_epoch<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    let current_epoch = state.get_current_epoch();
+    state.build_committee_cache(state.get_previous_epoch())?;
+    state.build_committee_cache(current_epoch)?;
+    state.build_committee_cache(current_epoch + 1)?;
+
+    process_justification_and_finalization(state)?;
+    process_crosslinks(state)?;
+    process_rewards_and_penalties(state)?;
+    process_registry_updates(state)?;
+    process_slashings(state)?;
+    process_final_updates(state)
+}
+
+pub fn process_justification_and_finalization<C: Config>(
+    state: &mut BeaconState<C>,
+) -> Result<(), Error> {
+    let current_epoch = state.get_current_epoch();
+    if current_epoch <= C::genesis_epoch() + 1 {
+        return Ok(());
+    }
+
+    let previous_epoch = state.get_previous_epoch();
+    let old_previous_justified_checkpoint = state.previous_justified_checkpoint.clone();
+    let old_current_justified_checkpoint = state.current_justified_checkpoint.clone();
+
+    // Process justifications.
+    state.previous_justified_checkpoint = state.current_justified_checkpoint.clone();
+    let bits_length = consts::JUSTIFICATION_BITS_LENGTH;
+    let mut bits = (0..bits_length)
+        .map(|i| state.justification_bits.get(i).unwrap_or(false))
+        .collect::<Vec<_>>();
+    bits.rotate_right(1);
+    bits[0] = false;
+
+    let total_active_balance = state.get_total_active_balance()?;
+    let previous_target_balance =
+        get_attesting_balance(state, &get_matching_target_attestations(state, previous_epoch)?)?;
+    if previous_target_balance * 3 >= total_active_balance * 2 {
+        state.current_justified_checkpoint = Checkpoint {
+            epoch: previous_epoch,
+            root: state.get_block_root(previous_epoch)?,
+        };
+        bits[1] = true;
+    }
+    let current_target_balance =
+        get_attesting_balance(state, &get_matching_target_attestations(state, current_epoch)?)?;
+    if current_target_balance * 3 >= total_active_balance * 2 {
+        state.current_justified_checkpoint = Checkpoint {
+            epoch: current_epoch,
+            root: state.get_block_root(current_epoch)?,
+        };
+        bits[0] = true;
+    }
+
+    for (i, bit) in bits.iter().enumerate() {
+        // The index is always below `JUSTIFICATION_BITS_LENGTH`.
+        let _ = state.justification_bits.set(i, *bit);
+    }
+
+    // Process finalizations.
+    // The 2nd/3rd/4th most recent epochs are justified, the 2nd using the 4th as source.
+    if bits[1..4].iter().all(|bit| *bit)
+        && old_previous_justified_checkpoint.epoch + 3 == current_epoch
+    {
+        state.finalized_checkpoint = old_previous_justified_checkpoint.clone();
+    }
+    // The 2nd/3rd most recent epochs are justified, the 2nd using the 3rd as source.
+    if bits[1..3].iter().all(|bit| *bit)
+        && old_previous_justified_checkpoint.epoch + 2 == current_epoch
+    {
+        state.finalized_checkpoint = old_previous_justified_checkpoint;
+    }
+    // The 1st/2nd/3rd most recent epochs are justified, the 1st using the 3rd as source.
+    if bits[0..3].iter().all(|bit| *bit)
+        && old_current_justified_checkpoint.epoch + 2 == current_epoch
+    {
+        state.finalized_checkpoint = old_current_justified_checkpoint.clone();
+    }
+    // The 1st/2nd most recent epochs are justified, the 1st using the 2nd as source.
+    if bits[0..2].iter().all(|bit| *bit)
+        && old_current_justified_checkpoint.epoch + 1 == current_epoch
+    {
+        state.finalized_checkpoint = old_current_justified_checkpoint;
+    }
+
+    Ok(())
+}
+
+pub fn process_crosslinks<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    state.previous_crosslinks = state.current_crosslinks.clone();
+
+    for epoch in &[state.get_previous_epoch(), state.get_current_epoch()] {
+        let epoch = *epoch;
+        let start_shard = committees::get_start_shard(state, epoch)?;
+        for offset in 0..committees::get_committee_count(state, epoch) {
+            let shard = (start_shard + offset) % C::ShardCount::to_u64();
+            let crosslink_committee = committees::get_crosslink_committee(state, epoch, shard)?;
+            let (winning_crosslink, attesting_indices) =
+                get_winning_crosslink_and_attesting_indices(state, epoch, shard)?;
+
+            if state.get_total_balance(&attesting_indices)? * 3
+                >= state.get_total_balance(&crosslink_committee)? * 2
+            {
+                state.current_crosslinks[shard as usize] = winning_crosslink;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn process_rewards_and_penalties<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    if state.get_current_epoch() == C::genesis_epoch() {
+        return Ok(());
+    }
+
+    let (attestation_rewards, attestation_penalties) = get_attestation_deltas(state)?;
+    let (crosslink_rewards, crosslink_penalties) = get_crosslink_deltas(state)?;
+    for index in 0..state.validators.len() {
+        let validator_index = ValidatorIndex::new(index as u64);
+        state.increase_balance(
+            validator_index,
+            attestation_rewards[index] + crosslink_rewards[index],
+        )?;
+        state.decrease_balance(
+            validator_index,
+            attestation_penalties[index] + crosslink_penalties[index],
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn process_registry_updates<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    let current_epoch = state.get_current_epoch();
+
+    // Process activation eligibility and ejections.
+    for index in 0..state.validators.len() {
+        let validator = &mut state.validators[index];
+        if validator.activation_eligibility_epoch == consts::FAR_FUTURE_EPOCH
+            && validator.effective_balance == C::max_effective_balance()
+        {
+            validator.activation_eligibility_epoch = current_epoch;
+        }
+
+        if validator.is_active_validator(current_epoch)
+            && validator.effective_balance <= C::ejection_balance()
+        {
+            state.initiate_validator_exit(ValidatorIndex::new(index as u64))?;
+        }
+    }
+
+    // Queue validators eligible for activation and not dequeued for activation prior to the
+    // finalized epoch.
+    let activation_exit_epoch =
+        misc::compute_activation_exit_epoch::<C>(state.finalized_checkpoint.epoch);
+    let mut activation_queue = state
+        .validators
+        .iter()
+        .enumerate()
+        .filter(|(_, validator)| {
+            validator.activation_eligibility_epoch != consts::FAR_FUTURE_EPOCH
+                && validator.activation_epoch >= activation_exit_epoch
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    activation_queue.sort_by_key(|index| state.validators[*index].activation_eligibility_epoch);
+
+    // Dequeue validators for activation up to the churn limit (without resetting activation
+    // epoch).
+    let churn_limit = state.get_validator_churn_limit() as usize;
+    for index in activation_queue.into_iter().take(churn_limit) {
+        let validator = &mut state.validators[index];
+        if validator.activation_epoch == consts::FAR_FUTURE_EPOCH {
+            validator.activation_epoch = misc::compute_activation_exit_epoch::<C>(current_epoch);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn process_slashings<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    let epoch = state.get_current_epoch();
+    let total_balance = state.get_total_active_balance()?;
+    let slashings_sum = state.slashings.iter().sum::<Gwei>();
+
+    for index in 0..state.validators.len() {
+        let validator = &state.validators[index];
+        if validator.slashed
+            && epoch + C::EpochsPerSlashingsVector::to_u64() / 2 == validator.withdrawable_epoch
+        {
+            // Factored out from the penalty numerator to avoid overflow.
+            let increment = C::effective_balance_increment();
+            let penalty_numerator = validator.effective_balance / increment
+                * cmp::min(slashings_sum * 3, total_balance);
+            let penalty = penalty_numerator / total_balance * increment;
+            state.decrease_balance(ValidatorIndex::new(index as u64), penalty)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn process_final_updates<C: Config>(state: &mut BeaconState<C>) -> Result<(), Error> {
+    let current_epoch = state.get_current_epoch();
+    let next_epoch = current_epoch + 1;
+
+    // Reset eth1 data votes.
+    let slots_per_eth1_voting_period = C::SlotsPerEth1VotingPeriod::to_u64();
+    if state.slot % slots_per_eth1_voting_period == slots_per_eth1_voting_period - 1 {
+        state.eth1_data_votes = VariableList::default();
+    }
+
+    // Update effective balances with hysteresis.
+    let half_increment = C::effective_balance_increment() / 2;
+    for (validator, balance) in state.validators.iter_mut().zip(state.balances.iter()) {
+        let balance = *balance;
+        if balance < validator.effective_balance
+            || validator.effective_balance + half_increment * 3 < balance
+        {
+            validator.effective_balance = cmp::min(
+                balance - balance % C::effective_balance_increment(),
+                C::max_effective_balance(),
+            );
+        }
+    }
+
+    // Set active index root.
+    let index_epoch = next_epoch + C::activation_exit_delay();
+    let indices_list = VariableList::<ValidatorIndex, C::ValidatorRegistryLimit>::from(
+        state.get_active_validator_indices(index_epoch),
+    );
+    state.active_index_roots[(index_epoch % C::EpochsPerHistoricalVector::to_u64()).as_usize()] =
+        H256::from_slice(&indices_list.tree_hash_root());
+
+    // Set committees root.
+    state.compact_committees_roots[(next_epoch % C::EpochsPerHistoricalVector::to_u64()).as_usize()] =
+        get_compact_committees_root(state, next_epoch)?;
+
+    // Reset slashings.
+    state.slashings[(next_epoch % C::EpochsPerSlashingsVector::to_u64()).as_usize()] = Gwei::new(0);
+
+    // Set randao mix.
+    state.randao_mixes[(next_epoch % C::EpochsPerHistoricalVector::to_u64()).as_usize()] =
+        state.get_randao_mix(current_epoch)?;
+
+    // Set historical root accumulator.
+    let epochs_per_historical_root =
+        C::SlotsPerHistoricalRoot::to_u64() / C::SlotsPerEpoch::to_u64();
+    if next_epoch
+        .as_u64()
+        .is_multiple_of(epochs_per_historical_root)
+    {
+        let historical_batch = HistoricalBatch::<C> {
+            block_roots: state.block_roots.clone(),
+            state_roots: state.state_roots.clone(),
+        };
+        state
+            .historical_roots
+            .push(H256::from_slice(&historical_batch.tree_hash_root()))
+            .map_err(|_| Error::HistoricalRootsFull)?;
+    }
+
+    // Update start shard.
+    state.start_shard =
+        (state.start_shard + committees::get_shard_delta(state, current_epoch))
+            % C::ShardCount::to_u64();
+
+    // Rotate current/previous epoch attestations.
+    state.previous_epoch_attestations = mem::take(&mut state.current_epoch_attestations);
+
+    Ok(())
+}
+
+pub fn get_attestation_deltas<C: Config>(
+    state: &BeaconState<C>,
+) -> Result<(Vec<Gwei>, Vec<Gwei>), Error> {
+    let previous_epoch = state.get_previous_epoch();
+    let total_balance = state.get_total_active_balance()?;
+    let validator_count = state.validators.len();
+    let mut rewards = vec![Gwei::new(0); validator_count];
+    let mut penalties = vec![Gwei::new(0); validator_count];
+
+    let eligible_validator_indices = state
+        .validators
+        .iter()
+        .enumerate()
+        .filter(|(_, validator)| {
+            validator.is_active_validator(previous_epoch)
+                || (validator.slashed && previous_epoch + 1 < validator.withdrawable_epoch)
+        })
+        .map(|(index, _)| ValidatorIndex::new(index as u64))
+        .collect::<Vec<_>>();
+
+    // Micro-incentives for matching FFG source, FFG target, and head.
+    let matching_source_attestations = get_matching_source_attestations(state, previous_epoch);
+    let matching_target_attestations = get_matching_target_attestations(state, previous_epoch)?;
+    let matching_head_attestations = get_matching_head_attestations(state, previous_epoch)?;
+    for attestations in &[
+        matching_source_attestations,
+        &matching_target_attestations[..],
+        &matching_head_attestations[..],
+    ] {
+        let unslashed_attesting_indices = get_unslashed_attesting_indices(state, attestations)?;
+        let attesting_balance = state.get_total_balance(&unslashed_attesting_indices)?;
+        for index in &eligible_validator_indices {
+            let base_reward = get_base_reward(state, *index, total_balance)?;
+            if unslashed_attesting_indices.contains(index) {
+                rewards[index.as_usize()] +=
+                    get_proportional_reward(base_reward, attesting_balance, total_balance);
+            } else {
+                penalties[index.as_usize()] += base_reward;
+            }
+        }
+    }
+
+    // Proposer and inclusion delay micro-rewards.
+    // Like the spec's `min(..., key=lambda a: a.inclusion_delay)`, a tie keeps the attestation
+    // that comes first in the list.
+    let mut earliest_attestations = BTreeMap::<ValidatorIndex, &PendingAttestation<C>>::new();
+    for attestation in matching_source_attestations {
+        let attesting_indices = committees::get_attesting_indices(
+            state,
+            &attestation.data,
+            &attestation.aggregation_bits,
+        )?;
+        for index in attesting_indices {
+            let earliest = earliest_attestations.entry(index).or_insert(attestation);
+            if attestation.inclusion_delay < earliest.inclusion_delay {
+                *earliest = attestation;
+            }
+        }
+    }
+
+    for (index, attestation) in earliest_attestations {
+        if state.validator(index)?.slashed {
+            continue;
+        }
+
+        let base_reward = get_base_reward(state, index, total_balance)?;
+        let proposer_reward = base_reward / C::proposer_reward_quotient();
+        rewards[attestation.proposer_index.as_usize()] += proposer_reward;
+
+        let max_attester_reward = base_reward - proposer_reward;
+        let slots_per_epoch = C::SlotsPerEpoch::to_u64();
+        rewards[index.as_usize()] += max_attester_reward
+            * (slots_per_epoch + C::min_attestation_inclusion_delay()
+                - attestation.inclusion_delay.as_u64())
+            / slots_per_epoch;
+    }
+
+    // Inactivity penalty.
+    let finality_delay = previous_epoch - state.finalized_checkpoint.epoch;
+    if finality_delay > C::min_epochs_to_inactivity_penalty() {
+        let matching_target_attesting_indices =
+            get_unslashed_attesting_indices(state, &matching_target_attestations)?;
+        for index in &eligible_validator_indices {
+            penalties[index.as_usize()] +=
+                get_base_reward(state, *index, total_balance)? * consts::BASE_REWARDS_PER_EPOCH;
+            if !matching_target_attesting_indices.contains(index) {
+                penalties[index.as_usize()] += state.validator(*index)?.effective_balance
+                    * finality_delay.as_u64()
+                    / C::inactivity_penalty_quotient();
+            }
+        }
+    }
+
+    Ok((rewards, penalties))
+}
+
+pub fn get_crosslink_deltas<C: Config>(
+    state: &BeaconState<C>,
+) -> Result<(Vec<Gwei>, Vec<Gwei>), Error> {
+    let total_balance = state.get_total_active_balance()?;
+    let validator_count = state.validators.len();
+    let mut rewards = vec![Gwei::new(0); validator_count];
+    let mut penalties = vec![Gwei::new(0); validator_count];
+
+    let epoch = state.get_previous_epoch();
+    let start_shard = committees::get_start_shard(state, epoch)?;
+    for offset in 0..committees::get_committee_count(state, epoch) {
+        let shard = (start_shard + offset) % C::ShardCount::to_u64();
+        let crosslink_committee = committees::get_crosslink_committee(state, epoch, shard)?;
+        let (_, attesting_indices) =
+            get_winning_crosslink_and_attesting_indices(state, epoch, shard)?;
+        let attesting_balance = state.get_total_balance(&attesting_indices)?;
+        let committee_balance = state.get_total_balance(&crosslink_committee)?;
+
+        for index in crosslink_committee {
+            let base_reward = get_base_reward(state, index, total_balance)?;
+            if attesting_indices.contains(&index) {
+                rewards[index.as_usize()] +=
+                    get_proportional_reward(base_reward, attesting_balance, committee_balance);
+            } else {
+                penalties[index.as_usize()] += base_reward;
+            }
+        }
+    }
+
+    Ok((rewards, penalties))
+}
+
+fn get_base_reward<C: Config>(
+    state: &BeaconState<C>,
+    index: ValidatorIndex,
+    total_balance: Gwei,
+) -> Result<Gwei, Error> {
+    let effective_balance = state.validator(index)?.effective_balance;
+
+    Ok(effective_balance * C::base_reward_factor()
+        / misc::integer_squareroot(total_balance.as_u64())
+        / consts::BASE_REWARDS_PER_EPOCH)
+}
+
+// `base_reward * attesting_balance` overflows `u64` once enough stake attests, and `Gwei`
+// multiplication saturates, so the product is taken in `u128`.
+fn get_proportional_reward(
+    base_reward: Gwei,
+    attesting_balance: Gwei,
+    total_balance: Gwei,
+) -> Gwei {
+    let reward = u128::from(base_reward.as_u64()) * u128::from(attesting_balance.as_u64())
+        / u128::from(total_balance.as_u64());
+    Gwei::new(u64::try_from(reward).unwrap_or(u64::MAX))
+}
+
+fn get_matching_source_attestations<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+) -> &[PendingAttestation<C>] {
+    if epoch == state.get_current_epoch() {
+        &state.current_epoch_attestations
+    } else {
+        &state.previous_epoch_attestations
+    }
+}
+
+fn get_matching_target_attestations<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+) -> Result<Vec<PendingAttestation<C>>, Error> {
+    let block_root = state.get_block_root(epoch)?;
+
+    Ok(get_matching_source_attestations(state, epoch)
+        .iter()
+        .filter(|attestation| attestation.data.target.root == block_root)
+        .cloned()
+        .collect())
+}
+
+fn get_matching_head_attestations<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+) -> Result<Vec<PendingAttestation<C>>, Error> {
+    let mut attestations = vec![];
+    for attestation in get_matching_source_attestations(state, epoch) {
+        let attestation_slot = committees::get_attestation_data_slot(state, &attestation.data)?;
+        if attestation.data.beacon_block_root == state.get_block_root_at_slot(attestation_slot)? {
+            attestations.push(attestation.clone());
+        }
+    }
+
+    Ok(attestations)
+}
+
+fn get_unslashed_attesting_indices<C: Config>(
+    state: &BeaconState<C>,
+    attestations: &[PendingAttestation<C>],
+) -> Result<BTreeSet<ValidatorIndex>, Error> {
+    let mut output = BTreeSet::new();
+    for attestation in attestations {
+        output.extend(committees::get_attesting_indices(
+            state,
+            &attestation.data,
+            &attestation.aggregation_bits,
+        )?);
+    }
+
+    let mut unslashed = BTreeSet::new();
+    for index in output {
+        if !state.validator(index)?.slashed {
+            unslashed.insert(index);
+        }
+    }
+
+    Ok(unslashed)
+}
+
+fn get_attesting_balance<C: Config>(
+    state: &BeaconState<C>,
+    attestations: &[PendingAttestation<C>],
+) -> Result<Gwei, Error> {
+    Ok(state.get_total_balance(&get_unslashed_attesting_indices(state, attestations)?)?)
+}
+
+fn get_winning_crosslink_and_attesting_indices<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+    shard: Shard,
+) -> Result<(Crosslink, BTreeSet<ValidatorIndex>), Error> {
+    let attestations = get_matching_source_attestations(state, epoch)
+        .iter()
+        .filter(|attestation| attestation.data.crosslink.shard == shard)
+        .cloned()
+        .collect::<Vec<_>>();
+    let current_crosslink_root =
+        H256::from_slice(&state.current_crosslinks[shard as usize].tree_hash_root());
+
+    // The winning crosslink has the most balance voting for it, with ties broken by the
+    // lexicographically largest data root.
+    let mut winning: Option<(Gwei, &Crosslink)> = None;
+    for attestation in &attestations {
+        let crosslink = &attestation.data.crosslink;
+        let is_candidate = crosslink.parent_root == current_crosslink_root
+            || H256::from_slice(&crosslink.tree_hash_root()) == current_crosslink_root;
+        if !is_candidate {
+            continue;
+        }
+
+        let supporting_attestations = attestations
+            .iter()
+            .filter(|other| other.data.crosslink == *crosslink)
+            .cloned()
+            .collect::<Vec<_>>();
+        let balance = get_attesting_balance(state, &supporting_attestations)?;
+        let is_better = winning.is_none_or(|(winning_balance, winning_crosslink)| {
+            (balance, crosslink.data_root) > (winning_balance, winning_crosslink.data_root)
+        });
+        if is_better {
+            winning = Some((balance, crosslink));
+        }
+    }
+
+    let winning_crosslink = winning
+        .map(|(_, crosslink)| crosslink.clone())
+        .unwrap_or_default();
+    let winning_attestations = attestations
+        .iter()
+        .filter(|attestation| attestation.data.crosslink == winning_crosslink)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    Ok((
+        winning_crosslink,
+        get_unslashed_attesting_indices(state, &winning_attestations)?,
+    ))
+}
+
+pub(crate) fn get_compact_committees_root<C: Config>(
+    state: &BeaconState<C>,
+    epoch: Epoch,
+) -> Result<H256, Error> {
+    let mut committees = vec![CompactCommittee::<C>::default(); C::ShardCount::to_usize()];
+    let start_shard = committees::get_start_shard(state, epoch)?;
+    for committee_number in 0..committees::get_committee_count(state, epoch) {
+        let shard = (start_shard + committee_number) % C::ShardCount::to_u64();
+        let committee = &mut committees[shard as usize];
+        for index in committees::get_crosslink_committee(state, epoch, shard)? {
+            let validator = state.validator(index)?;
+            let compact_balance = validator.effective_balance / C::effective_balance_increment();
+            // `index` (top 6 bytes) + `slashed` (16th bit) + `compact_balance` (bottom 15 bits).
+            let compact_validator = (index.as_u64() << 16)
+                + (u64::from(validator.slashed) << 15)
+                + compact_balance.as_u64();
+
+            // A committee never exceeds `MaxValidatorsPerCommittee`.
+            let _ = committee.pubkeys.push(validator.pubkey.clone());
+            let _ = committee.compact_validators.push(compact_validator);
+        }
+    }
+
+    Ok(H256::from_slice(
+        &FixedVector::<CompactCommittee<C>, C::ShardCount>::from(committees).tree_hash_root(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinimalConfig;
+    use crate::test_utils::state_with_validators;
+    use ssz_types::BitList;
+
+    #[test]
+    fn registry_updates_mark_eligibility_and_eject() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        state.validators[0].activation_eligibility_epoch = consts::FAR_FUTURE_EPOCH;
+        state.validators[0].activation_epoch = consts::FAR_FUTURE_EPOCH;
+        state.validators[1].effective_balance = MinimalConfig::ejection_balance();
+
+        process_registry_updates(&mut state).expect("registry updates succeed");
+
+        assert_eq!(state.validators[0].activation_eligibility_epoch, 0);
+        assert_eq!(
+            state.validators[0].activation_epoch,
+            misc::compute_activation_exit_epoch::<MinimalConfig>(Epoch::new(0))
+        );
+        assert_ne!(state.validators[1].exit_epoch, consts::FAR_FUTURE_EPOCH);
+        assert_eq!(state.validators[2].exit_epoch, consts::FAR_FUTURE_EPOCH);
+    }
+
+    #[test]
+    fn slashings_penalize_slashed_validators() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        state.validators[0].slashed = true;
+        state.validators[0].withdrawable_epoch =
+            Epoch::new(<MinimalConfig as Config>::EpochsPerSlashingsVector::to_u64() / 2);
+        state.slashings[0] = MinimalConfig::max_effective_balance();
+
+        process_slashings(&mut state).expect("slashings succeed");
+
+        assert!(state.balances[0] < MinimalConfig::max_effective_balance());
+        assert_eq!(state.balances[1], MinimalConfig::max_effective_balance());
+    }
+
+    #[test]
+    fn final_updates_apply_effective_balance_hysteresis() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        let increment = MinimalConfig::effective_balance_increment();
+        state.balances[0] -= increment / 4;
+        state.balances[1] -= increment * 2;
+
+        process_final_updates(&mut state).expect("final updates succeed");
+
+        assert_eq!(
+            state.validators[0].effective_balance,
+            MinimalConfig::max_effective_balance() - increment
+        );
+        assert_eq!(
+            state.validators[1].effective_balance,
+            MinimalConfig::max_effective_balance() - increment * 2
+        );
+        assert_eq!(
+            state.validators[2].effective_balance,
+            MinimalConfig::max_effective_balance()
+        );
+    }
+
+    #[test]
+    fn justification_is_skipped_in_first_epochs() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        state.slot = Slot::new(<MinimalConfig as Config>::SlotsPerEpoch::to_u64());
+        let expected = state.clone();
+
+        process_justification_and_finalization(&mut state).expect("justification succeeds");
+
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn absent_attesters_are_penalized() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        state.slot = Slot::new(<MinimalConfig as Config>::SlotsPerEpoch::to_u64());
+
+        let (rewards, penalties) = get_attestation_deltas(&state).expect("deltas are computable");
+
+        assert!(rewards.iter().all(|reward| *reward == 0));
+        assert!(penalties.iter().all(|penalty| *penalty > 0));
+    }
+
+    #[test]
+    fn inclusion_rewards_go_to_first_earliest_attestation() {
+        let mut state = state_with_validators::<MinimalConfig>(16);
+        state.slot = Slot::new(<MinimalConfig as Config>::SlotsPerEpoch::to_u64());
+        let epoch = Epoch::new(0);
+        let shard = committees::get_start_shard(&state, epoch).expect("epoch is in range");
+        let committee =
+            committees::get_crosslink_committee(&state, epoch, shard).expect("committee exists");
+        let proposers = (0..16)
+            .map(ValidatorIndex::new)
+            .filter(|index| !committee.contains(index))
+            .take(3)
+            .collect::<Vec<_>>();
+
+        for (inclusion_delay, proposer_index) in [2, 1, 1].iter().zip(&proposers) {
+            let mut aggregation_bits =
+                BitList::with_capacity(committee.len()).expect("committee fits in the bitfield");
+            for position in 0..committee.len() {
+                aggregation_bits
+                    .set(position, true)
+                    .expect("position is in range");
+            }
+            let attestation = PendingAttestation {
+                aggregation_bits,
+                data: AttestationData {
+                    beacon_block_root: H256::zero(),
+                    source: Checkpoint::default(),
+                    target: Checkpoint {
+                        epoch,
+                        root: H256::zero(),
+                    },
+                    crosslink: Crosslink {
+                        shard,
+                        ..Crosslink::default()
+                    },
+                },
+                inclusion_delay: Slot::new(*inclusion_delay),
+                proposer_index: *proposer_index,
+            };
+            state
+                .previous_epoch_attestations
+                .push(attestation)
+                .expect("list is not full");
+        }
+
+        let (rewards, _) = get_attestation_deltas(&state).expect("deltas are computable");
+
+        assert_eq!(rewards[proposers[0].as_usize()], 0);
+        assert!(rewards[proposers[1].as_usize()] > 0);
+        assert_eq!(rewards[proposers[2].as_usize()], 0);
+    }
+
+    #[test]
+    fn proportional_reward_does_not_overflow() {
+        // 4 million validators with 32 ETH each, half of which attest.
+        let total_balance = Gwei::new(4_000_000 * 32_000_000_000);
+
+        assert_eq!(
+            get_proportional_reward(Gwei::new(1_430), total_balance / 2, total_balance),
+            715
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use core::cmp;
+
+use eth2_hashing::hash;
+use ssz_types::{FixedVector, VariableList};
+use tree_hash::TreeHash;
+use typenum::Unsigned;
+
+use crate::beacon_state::{self, BeaconState};
+use crate::block_processing;
+use crate::config::Config;
+use crate::consts;
+use crate::epoch_processing;
+use crate::misc;
+use crate::primitives::*;
+use crate::types::*;
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    BlockProcessing(block_processing::Error),
+    EpochProcessing(epoch_processing::Error),
+    BeaconState(beacon_state::Error),
+}
+
+impl From<block_processing::Error> for Error {
+    fn from(error: block_processing::Error) -> Self {
+        Error::BlockProcessing(error)
+    }
+}
+
+impl From<epoch_processing::Error> for Error {
+    fn from(error: epoch_processing::Error) -> Self {
+        Error::EpochProcessing(error)
+    }
+}
+
+impl From<beacon_state::Error> for Error {
+    fn from(error: beacon_state::Error) -> Self {
+        Error::BeaconState(error)
+    }
+}
+
+pub fn initialize_beacon_state_from_eth1<C: Config>(
+    eth1_block_hash: H256,
+    eth1_timestamp: u64,
+    deposits: &[Deposit],
+) -> Result<BeaconState<C>, Error> {
+    let genesis_time =
+        eth1_timestamp - eth1_timestamp % consts::SECONDS_PER_DAY + 2 * consts::SECONDS_PER_DAY;
+    let mut state = BeaconState::<C> {
+        genesis_time,
+        eth1_data: Eth1Data {
+            block_hash: eth1_block_hash,
+            deposit_count: deposits.len() as u64,
+            ..Eth1Data::default()
+        },
+        latest_block_header: BeaconBlockHeader {
+            body_root: H256::from_slice(&BeaconBlockBody::<C>::default().tree_hash_root()),
+            ..BeaconBlockHeader::default()
+        },
+        randao_mixes: FixedVector::from(vec![
+            eth1_block_hash;
+            C::EpochsPerHistoricalVector::to_usize()
+        ]),
+        ..BeaconState::default()
+    };
+
+    // Process deposits, each against the root of the deposit list up to and including itself.
+    let mut deposit_tree = DepositTree::new();
+    for deposit in deposits {
+        deposit_tree.push(H256::from_slice(&deposit.data.tree_hash_root()));
+        state.eth1_data.deposit_root = deposit_tree.root();
+        block_processing::process_deposit(&mut state, deposit)?;
+    }
+
+    process_genesis_activations(&mut state)?;
+
+    Ok(state)
+}
+
+/// Activates every validator with a full effective balance and fills the historical index and
+/// committee roots, as done at the end of `initialize_beacon_state_from_eth1`.
+pub(crate) fn process_genesis_activations<C: Config>(
+    state: &mut BeaconState<C>,
+) -> Result<(), Error> {
+    for (validator, &balance) in state.validators.iter_mut().zip(state.balances.iter()) {
+        validator.effective_balance = cmp::min(
+            balance - balance % C::effective_balance_increment(),
+            C::max_effective_balance(),
+        );
+        if validator.effective_balance == C::max_effective_balance() {
+            validator.activation_eligibility_epoch = C::genesis_epoch();
+            validator.activation_epoch = C::genesis_epoch();
+        }
+    }
+
+    // Populate active_index_roots and compact_committees_roots. The committee cache only speeds up
+    // the compact committees root and is dropped before the index roots change the seed.
+    let indices_list = VariableList::<ValidatorIndex, C::ValidatorRegistryLimit>::from(
+        state.get_active_validator_indices(C::genesis_epoch()),
+    );
+    let active_index_root = H256::from_slice(&indices_list.tree_hash_root());
+    state.build_committee_cache(C::genesis_epoch())?;
+    let committee_root = epoch_processing::get_compact_committees_root(state, C::genesis_epoch())?;
+    state.drop_committee_caches();
+    for index in 0..C::EpochsPerHistoricalVector::to_usize() {
+        state.active_index_roots[index] = active_index_root;
+        state.compact_committees_roots[index] = committee_root;
+    }
+
+    Ok(())
+}
+
+pub fn is_valid_genesis_state<C: Config>(state: &BeaconState<C>) -> bool {
+    state.genesis_time >= C::min_genesis_time()
+        && state.get_active_validator_indices(C::genesis_epoch()).len() as u64
+            >= C::min_genesis_active_validator_count()
+}
+
+/// Incremental Merkle tree over deposit data roots, mirroring the deposit contract.
+///
+/// Recomputing the root of every deposit prefix from scratch would make genesis quadratic in the
+/// number of deposits; this keeps one node per level and produces each root in `O(depth)`.
+struct DepositTree {
+    branch: Vec<H256>,
+    zero_hashes: Vec<H256>,
+    count: u64,
+}
+
+impl DepositTree {
+    fn new() -> Self {
+        let depth = consts::DEPOSIT_CONTRACT_TREE_DEPTH as usize;
+        let mut zero_hashes = vec![H256::zero(); depth];
+        for height in 1..depth {
+            zero_hashes[height] = concat_hash(zero_hashes[height - 1], zero_hashes[height - 1]);
+        }
+
+        Self {
+            branch: vec![H256::zero(); depth],
+            zero_hashes,
+            count: 0,
+        }
+    }
+
+    fn push(&mut self, leaf: H256) {
+        self.count += 1;
+        let mut node = leaf;
+        let mut size = self.count;
+        for height in 0..self.branch.len() {
+            if size & 1 == 1 {
+                self.branch[height] = node;
+                return;
+            }
+            node = concat_hash(self.branch[height], node);
+            size /= 2;
+        }
+    }
+
+    fn root(&self) -> H256 {
+        let mut node = H256::zero();
+        let mut size = self.count;
+        for height in 0..self.branch.len() {
+            node = if size & 1 == 1 {
+                concat_hash(self.branch[height], node)
+            } else {
+                concat_hash(node, self.zero_hashes[height])
+            };
+            size /= 2;
+        }
+
+        concat_hash(node, H256::from(misc::int_to_bytes32(self.count)))
+    }
+}
+
+fn concat_hash(left: H256, right: H256) -> H256 {
+    H256::from_slice(&hash(&[left.as_bytes(), right.as_bytes()].concat()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MinimalConfig, QuickConfig};
+    use bls::{Keypair, PublicKeyBytes, Signature};
+
+    fn deposit_data<C: Config>(amount: Gwei) -> DepositData {
+        let keypair = Keypair::random();
+        let mut data = DepositData {
+            pubkey: PublicKeyBytes::from(keypair.pk.clone()),
+            withdrawal_credentials: H256::zero(),
+            amount,
+            signature: Signature::empty_signature(),
+        };
+        data.sign::<C>(&keypair.sk);
+        data
+    }
+
+    // Builds the proof of each deposit against the deposit list ending with that deposit.
+    fn deposits_with_proofs(data: Vec<DepositData>) -> Vec<Deposit> {
+        let depth = consts::DEPOSIT_CONTRACT_TREE_DEPTH as usize;
+        let zero_hashes = DepositTree::new().zero_hashes;
+        let leaves = data
+            .iter()
+            .map(|data| H256::from_slice(&data.tree_hash_root()))
+            .collect::<Vec<_>>();
+
+        data.iter()
+            .enumerate()
+            .map(|(index, data)| {
+                let mut proof = Vec::with_capacity(depth + 1);
+                let mut layer = leaves[..=index].to_vec();
+                for (height, zero_hash) in zero_hashes.iter().enumerate() {
+                    let sibling = (index >> height) ^ 1;
+                    proof.push(layer.get(sibling).copied().unwrap_or(*zero_hash));
+                    layer = layer
+                        .chunks(2)
+                        .map(|pair| concat_hash(pair[0], *pair.get(1).unwrap_or(zero_hash)))
+                        .collect();
+                }
+                proof.push(H256::from(misc::int_to_bytes32(index as u64 + 1)));
+
+                Deposit {
+                    proof: FixedVector::from(proof),
+                    data: data.clone(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn deposit_tree_matches_list_root() {
+        let mut tree = DepositTree::new();
+        let mut leaves = vec![];
+        for index in 0..5_u8 {
+            let leaf = H256::from([index + 1; 32]);
+            tree.push(leaf);
+            leaves.extend_from_slice(leaf.as_bytes());
+
+            let expected = tree_hash::mix_in_length(
+                &tree_hash::merkle_root(&leaves, 1 << consts::DEPOSIT_CONTRACT_TREE_DEPTH),
+                index as usize + 1,
+            );
+            assert_eq!(tree.root(), H256::from_slice(&expected));
+        }
+    }
+
+    #[test]
+    fn genesis_activates_full_deposits() {
+        let max = QuickConfig::max_effective_balance();
+        let deposits = deposits_with_proofs(vec![
+            deposit_data::<QuickConfig>(max),
+            deposit_data::<QuickConfig>(max - 1),
+            deposit_data::<QuickConfig>(max + 1),
+        ]);
+        let eth1_block_hash = H256::from([7; 32]);
+
+        let state = initialize_beacon_state_from_eth1::<QuickConfig>(
+            eth1_block_hash,
+            QuickConfig::min_genesis_time(),
+            &deposits,
+        )
+        .expect("genesis should succeed");
+
+        assert_eq!(state.genesis_time, 9_590_400);
+        assert_eq!(state.eth1_deposit_index, 3);
+        assert_eq!(state.eth1_data.deposit_count, 3);
+        assert_eq!(state.randao_mixes[0], eth1_block_hash);
+        assert_eq!(
+            state.get_active_validator_indices(Epoch::new(0)),
+            vec![ValidatorIndex::new(0), ValidatorIndex::new(2)]
+        );
+        assert_eq!(
+            state.validators[1].effective_balance,
+            max - QuickConfig::effective_balance_increment()
+        );
+        assert_eq!(state.active_index_roots[0], state.active_index_roots[1]);
+        assert!(is_valid_genesis_state(&state));
+    }
+
+    #[test]
+    fn genesis_rejects_bad_proof() {
+        let mut deposits = deposits_with_proofs(vec![deposit_data::<QuickConfig>(
+            QuickConfig::max_effective_balance(),
+        )]);
+        deposits[0].proof[0] = H256::from([1; 32]);
+
+        assert_eq!(
+            initialize_beacon_state_from_eth1::<QuickConfig>(H256::zero(), 0, &deposits),
+            Err(Error::BlockProcessing(
+                block_processing::Error::DepositInvalid {
+                    reason: block_processing::DepositInvalid::BadMerkleProof
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn genesis_state_needs_time_and_validators() {
+        let deposits = deposits_with_proofs(vec![deposit_data::<MinimalConfig>(
+            MinimalConfig::max_effective_balance(),
+        )]);
+        let state = initialize_beacon_state_from_eth1::<MinimalConfig>(
+            H256::zero(),
+            MinimalConfig::min_genesis_time(),
+            &deposits,
+        )
+        .expect("genesis should succeed");
+        assert!(state.genesis_time >= MinimalConfig::min_genesis_time());
+        assert!(!is_valid_genesis_state(&state));
+
+        let early_state =
+            initialize_beacon_state_from_eth1::<QuickConfig>(H256::zero(), 0, &deposits)
+                .expect("genesis should succeed");
+        assert_eq!(
+            early_state
+                .get_active_validator_indices(Epoch::new(0))
+                .len(),
+            1
+        );
+        assert!(!is_valid_genesis_state(&early_state));
+    }
+}
@@ -1,5 +1,22 @@
+// The SSZ collections come from the crate of the backend picked by the `ssz_new` feature.
+#[cfg(not(feature = "ssz_new"))]
+extern crate eth2_ssz_types as ssz_types;
+#[cfg(feature = "ssz_new")]
+extern crate ssz_new_types as ssz_types;
+
 pub mod beacon_state;
+pub mod block_processing;
+pub mod chain_spec;
+pub mod codec;
+pub mod committees;
 pub mod config;
 pub mod consts;
+pub mod epoch_processing;
+pub mod genesis;
+mod misc;
 pub mod primitives;
+mod serde_utils;
+pub mod signing;
+pub mod state_transition;
+pub mod test_utils;
 pub mod types;
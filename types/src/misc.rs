@@ -0,0 +1,77 @@
+use eth2_hashing::hash;
+
+use crate::config::Config;
+use crate::primitives::*;
+
+pub fn compute_activation_exit_epoch<C: Config>(epoch: Epoch) -> Epoch {
+    epoch + 1 + C::activation_exit_delay()
+}
+
+pub fn integer_squareroot(n: u64) -> u64 {
+    let mut x = n;
+    let mut y = x / 2 + x % 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+pub fn is_valid_merkle_branch(
+    leaf: H256,
+    branch: &[H256],
+    depth: u64,
+    index: u64,
+    root: H256,
+) -> bool {
+    if branch.len() as u64 != depth {
+        return false;
+    }
+
+    let mut value = leaf.as_bytes().to_vec();
+    for (i, node) in branch.iter().enumerate() {
+        value = if (index >> i) & 1 == 1 {
+            hash(&[node.as_bytes(), &value].concat())
+        } else {
+            hash(&[&value, node.as_bytes()].concat())
+        };
+    }
+
+    H256::from_slice(&value) == root
+}
+
+pub fn bytes_to_u64(bytes: &[u8]) -> u64 {
+    let mut array = [0; 8];
+    array.copy_from_slice(bytes);
+    u64::from_le_bytes(array)
+}
+
+pub fn int_to_bytes32(value: u64) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_squareroot_rounds_down() {
+        assert_eq!(integer_squareroot(0), 0);
+        assert_eq!(integer_squareroot(15), 3);
+        assert_eq!(integer_squareroot(16), 4);
+        assert_eq!(integer_squareroot(u64::MAX), u64::from(u32::MAX));
+    }
+
+    #[test]
+    fn merkle_branch_of_two_leaves() {
+        let left = H256::from([1; 32]);
+        let right = H256::from([2; 32]);
+        let root = H256::from_slice(&hash(&[left.as_bytes(), right.as_bytes()].concat()));
+
+        assert!(is_valid_merkle_branch(left, &[right], 1, 0, root));
+        assert!(is_valid_merkle_branch(right, &[left], 1, 1, root));
+        assert!(!is_valid_merkle_branch(left, &[right], 1, 1, root));
+    }
+}
@@ -1,10 +1,326 @@
-pub use bls::{PublicKey, SecretKey, Signature};
+use core::fmt;
+use core::iter::Sum;
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Rem, RemAssign, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+use ssz::{Decode, DecodeError, Encode};
+use tree_hash::{TreeHash, TreeHashType};
+use typenum::Unsigned;
+
+use crate::config::Config;
+
+pub use bls::{Keypair, PublicKey, SecretKey, Signature};
 pub use ethereum_types::H256;
 
-pub type Epoch = u64;
-pub type Gwei = u64;
 pub type Shard = u64;
-pub type Slot = u64;
-pub type ValidatorIndex = u64;
 pub type ValidatorId = PublicKey;
 pub type Version = [u8; 4];
+
+/// Defines a `u64` newtype that is encoded, hashed and serialized exactly like a `u64`.
+///
+/// Arithmetic operators saturate instead of overflowing; use the `checked_*` methods where
+/// overflow has to be detected. Division and remainder by zero panic as they do for `u64`.
+macro_rules! u64_newtype {
+    ($name: ident) => {
+        #[derive(
+            Clone,
+            Copy,
+            PartialEq,
+            Eq,
+            PartialOrd,
+            Ord,
+            Hash,
+            Default,
+            Debug,
+            Deserialize,
+            Serialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const fn new(value: u64) -> Self {
+                $name(value)
+            }
+
+            pub const fn max_value() -> Self {
+                $name(u64::MAX)
+            }
+
+            pub fn as_u64(self) -> u64 {
+                self.0
+            }
+
+            pub fn as_usize(self) -> usize {
+                self.0 as usize
+            }
+
+            pub fn checked_add<T: Into<$name>>(self, other: T) -> Option<Self> {
+                self.0.checked_add(other.into().0).map($name)
+            }
+
+            pub fn checked_sub<T: Into<$name>>(self, other: T) -> Option<Self> {
+                self.0.checked_sub(other.into().0).map($name)
+            }
+
+            pub fn checked_mul<T: Into<$name>>(self, other: T) -> Option<Self> {
+                self.0.checked_mul(other.into().0).map($name)
+            }
+
+            pub fn checked_div<T: Into<$name>>(self, other: T) -> Option<Self> {
+                self.0.checked_div(other.into().0).map($name)
+            }
+
+            pub fn saturating_add<T: Into<$name>>(self, other: T) -> Self {
+                $name(self.0.saturating_add(other.into().0))
+            }
+
+            pub fn saturating_sub<T: Into<$name>>(self, other: T) -> Self {
+                $name(self.0.saturating_sub(other.into().0))
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<u64> for $name {
+            fn eq(&self, other: &u64) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialOrd<u64> for $name {
+            fn partial_cmp(&self, other: &u64) -> Option<core::cmp::Ordering> {
+                self.0.partial_cmp(other)
+            }
+        }
+
+        impl<T: Into<$name>> Add<T> for $name {
+            type Output = Self;
+
+            fn add(self, other: T) -> Self {
+                self.saturating_add(other)
+            }
+        }
+
+        impl<T: Into<$name>> AddAssign<T> for $name {
+            fn add_assign(&mut self, other: T) {
+                *self = *self + other;
+            }
+        }
+
+        impl<T: Into<$name>> Sub<T> for $name {
+            type Output = Self;
+
+            fn sub(self, other: T) -> Self {
+                self.saturating_sub(other)
+            }
+        }
+
+        impl<T: Into<$name>> SubAssign<T> for $name {
+            fn sub_assign(&mut self, other: T) {
+                *self = *self - other;
+            }
+        }
+
+        impl<T: Into<$name>> Mul<T> for $name {
+            type Output = Self;
+
+            fn mul(self, other: T) -> Self {
+                $name(self.0.saturating_mul(other.into().0))
+            }
+        }
+
+        impl<T: Into<$name>> MulAssign<T> for $name {
+            fn mul_assign(&mut self, other: T) {
+                *self = *self * other;
+            }
+        }
+
+        impl<T: Into<$name>> Div<T> for $name {
+            type Output = Self;
+
+            fn div(self, other: T) -> Self {
+                $name(self.0 / other.into().0)
+            }
+        }
+
+        impl<T: Into<$name>> DivAssign<T> for $name {
+            fn div_assign(&mut self, other: T) {
+                *self = *self / other;
+            }
+        }
+
+        impl<T: Into<$name>> Rem<T> for $name {
+            type Output = Self;
+
+            fn rem(self, other: T) -> Self {
+                $name(self.0 % other.into().0)
+            }
+        }
+
+        impl<T: Into<$name>> RemAssign<T> for $name {
+            fn rem_assign(&mut self, other: T) {
+                *self = *self % other;
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($name(0), Add::add)
+            }
+        }
+
+        impl<'a> Sum<&'a $name> for $name {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Encode for $name {
+            fn is_ssz_fixed_len() -> bool {
+                <u64 as Encode>::is_ssz_fixed_len()
+            }
+
+            fn ssz_fixed_len() -> usize {
+                <u64 as Encode>::ssz_fixed_len()
+            }
+
+            fn ssz_bytes_len(&self) -> usize {
+                self.0.ssz_bytes_len()
+            }
+
+            fn ssz_append(&self, buf: &mut Vec<u8>) {
+                self.0.ssz_append(buf)
+            }
+        }
+
+        impl Decode for $name {
+            fn is_ssz_fixed_len() -> bool {
+                <u64 as Decode>::is_ssz_fixed_len()
+            }
+
+            fn ssz_fixed_len() -> usize {
+                <u64 as Decode>::ssz_fixed_len()
+            }
+
+            fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+                u64::from_ssz_bytes(bytes).map($name)
+            }
+        }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::SszType for $name {
+            fn is_variable_size() -> bool {
+                <u64 as ssz_new::SszType>::is_variable_size()
+            }
+
+            fn fixed_length() -> usize {
+                <u64 as ssz_new::SszType>::fixed_length()
+            }
+        }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::Serialize for $name {
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), ssz_new::Error> {
+                ssz_new::Serialize::serialize_into(&self.0, buffer)
+            }
+
+            fn serialized_len(&self) -> usize {
+                ssz_new::Serialize::serialized_len(&self.0)
+            }
+        }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::Deserialize for $name {
+            fn deserialize(bytes: &[u8]) -> Result<Self, ssz_new::Error> {
+                <u64 as ssz_new::Deserialize>::deserialize(bytes).map($name)
+            }
+        }
+
+        impl TreeHash for $name {
+            fn tree_hash_type() -> TreeHashType {
+                u64::tree_hash_type()
+            }
+
+            fn tree_hash_packed_encoding(&self) -> Vec<u8> {
+                self.0.tree_hash_packed_encoding()
+            }
+
+            fn tree_hash_packing_factor() -> usize {
+                u64::tree_hash_packing_factor()
+            }
+
+            fn tree_hash_root(&self) -> Vec<u8> {
+                self.0.tree_hash_root()
+            }
+        }
+    };
+}
+
+u64_newtype!(Slot);
+u64_newtype!(Epoch);
+u64_newtype!(Gwei);
+u64_newtype!(ValidatorIndex);
+
+impl Slot {
+    pub fn epoch<C: Config>(self) -> Epoch {
+        Epoch(self.0 / C::SlotsPerEpoch::to_u64())
+    }
+}
+
+impl Epoch {
+    pub fn start_slot<C: Config>(self) -> Slot {
+        Slot(self.0.saturating_mul(C::SlotsPerEpoch::to_u64()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinimalConfig;
+
+    #[test]
+    fn wire_format_matches_u64() {
+        let value = 0x0102_0304_0506_0708;
+        let slot = Slot::new(value);
+
+        assert_eq!(slot.as_ssz_bytes(), value.as_ssz_bytes());
+        assert_eq!(Slot::from_ssz_bytes(&value.as_ssz_bytes()), Ok(slot));
+        assert_eq!(slot.tree_hash_root(), value.tree_hash_root());
+        assert_eq!(
+            serde_yaml::to_string(&slot).expect("slot should serialize"),
+            serde_yaml::to_string(&value).expect("u64 should serialize")
+        );
+    }
+
+    #[test]
+    fn operators_saturate() {
+        assert_eq!(Epoch::new(1) - 2, Epoch::new(0));
+        assert_eq!(Epoch::max_value() + 1, Epoch::max_value());
+        assert_eq!(Gwei::max_value() * 2, Gwei::max_value());
+        assert_eq!(Gwei::new(1).checked_sub(2), None);
+        assert_eq!(Gwei::max_value().checked_add(1), None);
+    }
+
+    #[test]
+    fn slot_and_epoch_conversions() {
+        assert_eq!(Slot::new(17).epoch::<MinimalConfig>(), Epoch::new(2));
+        assert_eq!(Epoch::new(2).start_slot::<MinimalConfig>(), Slot::new(16));
+    }
+}
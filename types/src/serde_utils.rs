@@ -0,0 +1,55 @@
+//! Hex string (de)serialization for fixed-size byte arrays, matching the YAML format used by the
+//! eth2 spec tests.
+
+use serde::de::{Deserializer, Error};
+use serde::ser::Serializer;
+use serde_hex::{encode as hex_encode, PrefixedHexVisitor};
+
+use crate::primitives::Version;
+
+pub fn fork_version_from_hex_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Version, D::Error> {
+    let mut version = Version::default();
+    bytes_from_hex_str(deserializer, &mut version)?;
+    Ok(version)
+}
+
+pub fn fork_version_to_hex_str<S: Serializer>(
+    version: &Version,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex_encode(version))
+}
+
+pub fn graffiti_from_hex_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[u8; 32], D::Error> {
+    let mut graffiti = [0; 32];
+    bytes_from_hex_str(deserializer, &mut graffiti)?;
+    Ok(graffiti)
+}
+
+pub fn graffiti_to_hex_str<S: Serializer>(
+    graffiti: &[u8; 32],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&hex_encode(graffiti))
+}
+
+fn bytes_from_hex_str<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    bytes: &mut [u8],
+) -> Result<(), D::Error> {
+    let decoded = deserializer.deserialize_str(PrefixedHexVisitor)?;
+    if decoded.len() != bytes.len() {
+        return Err(D::Error::custom(format!(
+            "expected {} bytes, found {}",
+            bytes.len(),
+            decoded.len()
+        )));
+    }
+
+    bytes.copy_from_slice(&decoded);
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use bls::{AggregatePublicKey, AggregateSignature};
+use tree_hash::{SignedRoot, TreeHash};
+
+use crate::beacon_state::BeaconState;
+use crate::config::Config;
+use crate::primitives::*;
+use crate::types::*;
+
+pub fn compute_domain(domain_type: u64, fork_version: Version) -> u64 {
+    let mut bytes = [0; 8];
+    bytes[..4].copy_from_slice(&(domain_type as u32).to_le_bytes());
+    bytes[4..].copy_from_slice(&fork_version);
+    u64::from_le_bytes(bytes)
+}
+
+pub fn get_domain<C: Config>(
+    state: &BeaconState<C>,
+    domain_type: u64,
+    message_epoch: Option<Epoch>,
+) -> u64 {
+    let epoch = message_epoch.unwrap_or_else(|| state.get_current_epoch());
+    let fork_version = if epoch < state.fork.epoch {
+        state.fork.previous_version
+    } else {
+        state.fork.current_version
+    };
+
+    compute_domain(domain_type, fork_version)
+}
+
+impl<C: Config> BeaconBlock<C> {
+    pub fn sign(&mut self, secret_key: &SecretKey, state: &BeaconState<C>) {
+        self.signature = Signature::new(&self.signed_root(), self.domain(state), secret_key);
+    }
+
+    pub fn verify_signature(&self, pubkey: &PublicKey, state: &BeaconState<C>) -> bool {
+        self.signature
+            .verify(&self.signed_root(), self.domain(state), pubkey)
+    }
+
+    fn domain(&self, state: &BeaconState<C>) -> u64 {
+        get_domain(
+            state,
+            C::domain_beacon_proposer(),
+            Some(self.slot.epoch::<C>()),
+        )
+    }
+}
+
+impl VoluntaryExit {
+    pub fn sign<C: Config>(&mut self, secret_key: &SecretKey, state: &BeaconState<C>) {
+        let domain = get_domain(state, C::domain_voluntary_exit(), Some(self.epoch));
+        self.signature = Signature::new(&self.signed_root(), domain, secret_key);
+    }
+
+    pub fn verify_signature<C: Config>(&self, pubkey: &PublicKey, state: &BeaconState<C>) -> bool {
+        let domain = get_domain(state, C::domain_voluntary_exit(), Some(self.epoch));
+        self.signature.verify(&self.signed_root(), domain, pubkey)
+    }
+}
+
+impl Transfer {
+    pub fn sign<C: Config>(&mut self, secret_key: &SecretKey, state: &BeaconState<C>) {
+        let domain = get_domain(state, C::domain_transfer(), None);
+        self.signature = Signature::new(&self.signed_root(), domain, secret_key);
+    }
+
+    /// Checks the signature against `self.pubkey`, the key the sender withdraws with.
+    pub fn verify_signature<C: Config>(&self, state: &BeaconState<C>) -> bool {
+        let domain = get_domain(state, C::domain_transfer(), None);
+        self.signature
+            .verify(&self.signed_root(), domain, &self.pubkey)
+    }
+}
+
+impl DepositData {
+    pub fn sign<C: Config>(&mut self, secret_key: &SecretKey) {
+        self.signature = Signature::new(&self.signed_root(), deposit_domain::<C>(), secret_key);
+    }
+
+    pub fn verify_signature<C: Config>(&self, pubkey: &PublicKey) -> bool {
+        self.signature
+            .verify(&self.signed_root(), deposit_domain::<C>(), pubkey)
+    }
+}
+
+// Deposits are valid across forks, so the domain ignores the fork version.
+fn deposit_domain<C: Config>() -> u64 {
+    compute_domain(C::domain_deposit(), Version::default())
+}
+
+// Phase 0 attesters sign `AttestationDataAndCustodyBit` with the custody bit unset rather than
+// the signed root of the `IndexedAttestation` itself.
+impl<C: Config> IndexedAttestation<C> {
+    pub fn sign(&mut self, secret_keys: &[&SecretKey], state: &BeaconState<C>) {
+        let message = self.message();
+        let domain = self.domain(state);
+
+        let mut aggregate_signature = AggregateSignature::new();
+        for secret_key in secret_keys {
+            aggregate_signature.add(&Signature::new(&message, domain, secret_key));
+        }
+        self.signature = Signature::from_bytes(&aggregate_signature.as_bytes())
+            .expect("an aggregate of signatures is a point on the curve, so its bytes decode");
+    }
+
+    pub fn verify_signature(
+        &self,
+        aggregate_pubkey: &AggregatePublicKey,
+        state: &BeaconState<C>,
+    ) -> bool {
+        AggregateSignature::from_bytes(&self.signature.as_bytes())
+            .map(|signature| {
+                signature.verify(&self.message(), self.domain(state), aggregate_pubkey)
+            })
+            .unwrap_or(false)
+    }
+
+    fn message(&self) -> Vec<u8> {
+        AttestationDataAndCustodyBit {
+            data: self.data.clone(),
+            custody_bit: false,
+        }
+        .tree_hash_root()
+    }
+
+    fn domain(&self, state: &BeaconState<C>) -> u64 {
+        get_domain(state, C::domain_attestation(), Some(self.data.target.epoch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinimalConfig;
+    use bls::Keypair;
+    use ssz_types::VariableList;
+
+    #[test]
+    fn domain_is_little_endian_type_then_version() {
+        assert_eq!(
+            compute_domain(3, [1, 2, 3, 4]),
+            u64::from_le_bytes([3, 0, 0, 0, 1, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn domain_uses_previous_version_before_fork_epoch() {
+        let state = BeaconState::<MinimalConfig> {
+            fork: Fork {
+                previous_version: [1; 4],
+                current_version: [2; 4],
+                epoch: Epoch::new(5),
+            },
+            ..BeaconState::default()
+        };
+
+        assert_eq!(
+            get_domain(&state, 4, Some(Epoch::new(4))),
+            compute_domain(4, [1; 4])
+        );
+        assert_eq!(
+            get_domain(&state, 4, Some(Epoch::new(5))),
+            compute_domain(4, [2; 4])
+        );
+        assert_eq!(get_domain(&state, 4, None), compute_domain(4, [1; 4]));
+    }
+
+    #[test]
+    fn voluntary_exit_signature_is_bound_to_fork() {
+        let keypair = Keypair::random();
+        let mut state = BeaconState::<MinimalConfig>::default();
+        let mut exit = VoluntaryExit {
+            epoch: Epoch::new(0),
+            validator_index: ValidatorIndex::new(0),
+            signature: Signature::empty_signature(),
+        };
+
+        exit.sign(&keypair.sk, &state);
+        assert!(exit.verify_signature(&keypair.pk, &state));
+
+        state.fork.current_version = [1; 4];
+        assert!(!exit.verify_signature(&keypair.pk, &state));
+    }
+
+    #[test]
+    fn deposit_data_signature_round_trips() {
+        let keypair = Keypair::random();
+        let mut data = DepositData {
+            pubkey: keypair.pk.clone().into(),
+            withdrawal_credentials: H256::zero(),
+            amount: MinimalConfig::max_effective_balance(),
+            signature: Signature::empty_signature(),
+        };
+
+        data.sign::<MinimalConfig>(&keypair.sk);
+        assert!(data.verify_signature::<MinimalConfig>(&keypair.pk));
+
+        data.amount += 1;
+        assert!(!data.verify_signature::<MinimalConfig>(&keypair.pk));
+    }
+
+    #[test]
+    fn indexed_attestation_signature_aggregates_keys() {
+        let keypairs = [Keypair::random(), Keypair::random()];
+        let state = BeaconState::<MinimalConfig>::default();
+        let mut attestation = IndexedAttestation::<MinimalConfig> {
+            custody_bit_0_indices: VariableList::from(vec![
+                ValidatorIndex::new(0),
+                ValidatorIndex::new(1),
+            ]),
+            custody_bit_1_indices: VariableList::default(),
+            data: AttestationData {
+                beacon_block_root: H256::zero(),
+                source: Default::default(),
+                target: Default::default(),
+                crosslink: Default::default(),
+            },
+            signature: Signature::empty_signature(),
+        };
+
+        attestation.sign(&[&keypairs[0].sk, &keypairs[1].sk], &state);
+
+        let mut aggregate_pubkey = AggregatePublicKey::new();
+        aggregate_pubkey.add(&keypairs[0].pk);
+        assert!(!attestation.verify_signature(&aggregate_pubkey, &state));
+
+        aggregate_pubkey.add(&keypairs[1].pk);
+        assert!(attestation.verify_signature(&aggregate_pubkey, &state));
+    }
+}
@@ -0,0 +1,166 @@
+use tree_hash::{SignedRoot, TreeHash};
+use typenum::Unsigned;
+
+use crate::beacon_state::{self, BeaconState};
+use crate::block_processing;
+use crate::config::Config;
+use crate::epoch_processing;
+use crate::primitives::*;
+use crate::types::BeaconBlock;
+
+#[derive(PartialEq, Debug)]
+pub enum Error {
+    SlotOutOfOrder { state_slot: Slot, target_slot: Slot },
+    StateRootMismatch { block: H256, computed: H256 },
+    BlockProcessing(block_processing::Error),
+    EpochProcessing(epoch_processing::Error),
+    BeaconState(beacon_state::Error),
+}
+
+impl From<beacon_state::Error> for Error {
+    fn from(error: beacon_state::Error) -> Self {
+        Error::BeaconState(error)
+    }
+}
+
+impl From<block_processing::Error> for Error {
+    fn from(error: block_processing::Error) -> Self {
+        Error::BlockProcessing(error)
+    }
+}
+
+impl From<epoch_processing::Error> for Error {
+    fn from(error: epoch_processing::Error) -> Self {
+        Error::EpochProcessing(error)
+    }
+}
+
+pub fn state_transition<C: Config>(
+    state: &mut BeaconState<C>,
+    block: &BeaconBlock<C>,
+    validate_signatures: bool,
+) -> Result<(), Error> {
+    process_slots(state, block.slot)?;
+    state.build_committee_cache(state.get_previous_epoch())?;
+    state.build_committee_cache(state.get_current_epoch())?;
+    block_processing::process_block(state, block, validate_signatures)?;
+
+    let computed = H256::from_slice(&state.tree_hash_root());
+    if block.state_root != computed {
+        return Err(Error::StateRootMismatch {
+            block: block.state_root,
+            computed,
+        });
+    }
+
+    Ok(())
+}
+
+pub fn process_slots<C: Config>(state: &mut BeaconState<C>, slot: Slot) -> Result<(), Error> {
+    if state.slot > slot {
+        return Err(Error::SlotOutOfOrder {
+            state_slot: state.slot,
+            target_slot: slot,
+        });
+    }
+
+    while state.slot < slot {
+        process_slot(state);
+        let slots_per_epoch = C::SlotsPerEpoch::to_u64();
+        if state.slot % slots_per_epoch == slots_per_epoch - 1 {
+            epoch_processing::process_epoch(state)?;
+        }
+        state.slot += 1;
+    }
+
+    Ok(())
+}
+
+pub fn process_slot<C: Config>(state: &mut BeaconState<C>) {
+    let index = (state.slot % C::SlotsPerHistoricalRoot::to_u64()).as_usize();
+
+    let previous_state_root = H256::from_slice(&state.tree_hash_root());
+    state.state_roots[index] = previous_state_root;
+
+    if state.latest_block_header.state_root.is_zero() {
+        state.latest_block_header.state_root = previous_state_root;
+    }
+
+    let previous_block_root = H256::from_slice(&state.latest_block_header.signed_root());
+    state.block_roots[index] = previous_block_root;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MinimalConfig, QuickConfig};
+    use crate::types::{AttestationData, PendingAttestation};
+    use ssz_types::BitList;
+
+    #[test]
+    fn process_slot_caches_roots() {
+        let mut state = BeaconState::<MinimalConfig>::default();
+        let state_root = H256::from_slice(&state.tree_hash_root());
+
+        process_slot(&mut state);
+
+        assert_eq!(state.state_roots[0], state_root);
+        assert_eq!(state.latest_block_header.state_root, state_root);
+        assert_eq!(
+            state.block_roots[0],
+            H256::from_slice(&state.latest_block_header.signed_root())
+        );
+    }
+
+    #[test]
+    fn process_slots_advances_slot() {
+        let mut state = BeaconState::<MinimalConfig>::default();
+
+        process_slots(&mut state, Slot::new(3)).expect("slots should be processed");
+
+        assert_eq!(state.slot, 3);
+        assert!(!state.state_roots[2].is_zero());
+        assert!(state.state_roots[3].is_zero());
+    }
+
+    #[test]
+    fn process_slots_rejects_past_slot() {
+        let mut state = BeaconState::<MinimalConfig> {
+            slot: Slot::new(5),
+            ..BeaconState::default()
+        };
+
+        assert_eq!(
+            process_slots(&mut state, Slot::new(4)),
+            Err(Error::SlotOutOfOrder {
+                state_slot: Slot::new(5),
+                target_slot: Slot::new(4),
+            })
+        );
+    }
+
+    #[test]
+    fn process_slots_rotates_attestations_at_epoch_boundary() {
+        let mut state = BeaconState::<QuickConfig>::default();
+        let attestation = PendingAttestation {
+            aggregation_bits: BitList::with_capacity(0).expect("capacity is within limit"),
+            data: AttestationData {
+                beacon_block_root: H256::zero(),
+                source: Default::default(),
+                target: Default::default(),
+                crosslink: Default::default(),
+            },
+            inclusion_delay: Slot::new(1),
+            proposer_index: ValidatorIndex::new(0),
+        };
+        state
+            .current_epoch_attestations
+            .push(attestation.clone())
+            .expect("list is not full");
+
+        process_slots(&mut state, Slot::new(1)).expect("slots should be processed");
+
+        assert!(state.current_epoch_attestations.is_empty());
+        assert_eq!(&state.previous_epoch_attestations[..], &[attestation]);
+    }
+}
@@ -0,0 +1,381 @@
+//! Fixtures for tests and benchmarks: deterministic keypairs and builders for states, blocks and
+//! attestations that pass the state transition.
+
+use eth2_hashing::hash;
+use ssz::Encode;
+use ssz_types::{BitList, VariableList};
+use tree_hash::{SignedRoot, TreeHash};
+use typenum::Unsigned;
+
+use crate::beacon_state::{self, BeaconState};
+use crate::block_processing;
+use crate::committees;
+use crate::config::Config;
+use crate::consts;
+use crate::genesis;
+use crate::primitives::*;
+use crate::signing;
+use crate::types::*;
+
+// Order of the BLS12-381 scalar field, big-endian.
+const CURVE_ORDER: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// Returns the interop keypair of `validator_index`.
+///
+/// The secret key is the SHA-256 hash of the little-endian index padded to 32 bytes, read as a
+/// little-endian integer and reduced modulo the curve order, which matches the keys used by other
+/// clients for interop testnets.
+pub fn generate_deterministic_keypair(validator_index: usize) -> Keypair {
+    let mut preimage = [0; 32];
+    preimage[..8].copy_from_slice(&(validator_index as u64).to_le_bytes());
+
+    let mut scalar = [0; 32];
+    scalar.copy_from_slice(&hash(&preimage));
+    scalar.reverse();
+    // A 256 bit value is less than three times the curve order.
+    while scalar >= CURVE_ORDER {
+        subtract_curve_order(&mut scalar);
+    }
+
+    let mut secret_key_bytes = [0; 48];
+    secret_key_bytes[16..].copy_from_slice(&scalar);
+    let sk = SecretKey::from_bytes(&secret_key_bytes).expect("scalar is below the curve order");
+    let pk = PublicKey::from_secret_key(&sk);
+
+    Keypair { sk, pk }
+}
+
+pub fn generate_deterministic_keypairs(validator_count: usize) -> Vec<Keypair> {
+    (0..validator_count)
+        .map(generate_deterministic_keypair)
+        .collect()
+}
+
+fn subtract_curve_order(scalar: &mut [u8; 32]) {
+    let mut borrow = 0;
+    for (byte, order_byte) in scalar.iter_mut().zip(CURVE_ORDER.iter()).rev() {
+        let difference = i16::from(*byte) - i16::from(*order_byte) - borrow;
+        borrow = i16::from(difference < 0);
+        *byte = (difference + (borrow << 8)) as u8;
+    }
+}
+
+/// Builds a genesis `BeaconState` in which every keypair belongs to an active validator with the
+/// maximum effective balance.
+///
+/// Validators are inserted directly rather than through deposits, so building large states does
+/// not verify a signature per validator.
+pub struct TestingBeaconStateBuilder<C: Config> {
+    state: BeaconState<C>,
+    keypairs: Vec<Keypair>,
+}
+
+impl<C: Config> TestingBeaconStateBuilder<C> {
+    pub fn from_deterministic_keypairs(validator_count: usize) -> Self {
+        Self::from_keypairs(generate_deterministic_keypairs(validator_count))
+    }
+
+    pub fn from_keypairs(keypairs: Vec<Keypair>) -> Self {
+        let mut state = BeaconState::<C> {
+            genesis_time: C::min_genesis_time(),
+            eth1_deposit_index: keypairs.len() as u64,
+            eth1_data: Eth1Data {
+                deposit_count: keypairs.len() as u64,
+                ..Eth1Data::default()
+            },
+            latest_block_header: BeaconBlockHeader {
+                body_root: H256::from_slice(&BeaconBlockBody::<C>::default().tree_hash_root()),
+                ..BeaconBlockHeader::default()
+            },
+            ..BeaconState::default()
+        };
+
+        for keypair in &keypairs {
+            let mut withdrawal_credentials = hash(&keypair.pk.as_ssz_bytes());
+            withdrawal_credentials[0] = C::bls_withdrawal_prefix_byte();
+
+            let validator = Validator {
+                pubkey: keypair.pk.clone(),
+                withdrawal_credentials: H256::from_slice(&withdrawal_credentials),
+                effective_balance: Gwei::new(0),
+                slashed: false,
+                activation_eligibility_epoch: consts::FAR_FUTURE_EPOCH,
+                activation_epoch: consts::FAR_FUTURE_EPOCH,
+                exit_epoch: consts::FAR_FUTURE_EPOCH,
+                withdrawable_epoch: consts::FAR_FUTURE_EPOCH,
+            };
+            // Both lists are bounded by `ValidatorRegistryLimit`, which no fixture reaches.
+            let _ = state.validators.push(validator);
+            let _ = state.balances.push(C::max_effective_balance());
+        }
+
+        Self { state, keypairs }
+    }
+
+    pub fn build(mut self) -> (BeaconState<C>, Vec<Keypair>) {
+        genesis::process_genesis_activations(&mut self.state)
+            .expect("a state without deposits has valid committees");
+
+        (self.state, self.keypairs)
+    }
+}
+
+/// Builds a signed `BeaconBlock` on top of a state that has been advanced to the block's slot with
+/// `state_transition::process_slots`.
+pub struct TestingBeaconBlockBuilder<C: Config> {
+    block: BeaconBlock<C>,
+}
+
+impl<C: Config> TestingBeaconBlockBuilder<C> {
+    pub fn new(state: &BeaconState<C>) -> Self {
+        Self {
+            block: BeaconBlock {
+                slot: state.slot,
+                parent_root: H256::from_slice(&state.latest_block_header.signed_root()),
+                state_root: H256::zero(),
+                body: BeaconBlockBody {
+                    eth1_data: state.eth1_data.clone(),
+                    ..BeaconBlockBody::default()
+                },
+                signature: Signature::empty_signature(),
+            },
+        }
+    }
+
+    pub fn insert_attestation(&mut self, attestation: Attestation<C>) -> &mut Self {
+        // Exceeding `MaxAttestations` is a bug in the calling test.
+        self.block
+            .body
+            .attestations
+            .push(attestation)
+            .expect("block has room for the attestation");
+        self
+    }
+
+    /// Signs the RANDAO reveal and the block with the proposer's key, filling in the state root the
+    /// block produces when applied to `state`.
+    pub fn build(
+        mut self,
+        proposer_key: &SecretKey,
+        state: &BeaconState<C>,
+    ) -> Result<BeaconBlock<C>, block_processing::Error> {
+        let epoch = state.get_current_epoch();
+        self.block.body.randao_reveal = Signature::new(
+            &epoch.tree_hash_root(),
+            signing::get_domain(state, C::domain_randao(), None),
+            proposer_key,
+        );
+
+        let mut post_state = state.clone();
+        block_processing::process_block(&mut post_state, &self.block, false)?;
+        self.block.state_root = H256::from_slice(&post_state.tree_hash_root());
+        self.block.sign(proposer_key, state);
+
+        Ok(self.block)
+    }
+}
+
+/// Builds an `Attestation` by the crosslink committee of `shard` in the state's current epoch,
+/// voting for the state's head block.
+pub struct TestingAttestationBuilder<C: Config> {
+    committee: Vec<ValidatorIndex>,
+    attestation: Attestation<C>,
+}
+
+impl<C: Config> TestingAttestationBuilder<C> {
+    pub fn new(state: &BeaconState<C>, shard: Shard) -> Result<Self, beacon_state::Error> {
+        let epoch = state.get_current_epoch();
+        let committee = committees::get_crosslink_committee(state, epoch, shard)?;
+
+        // The head block's state root is only filled in by the next `process_slot`.
+        let mut head = state.latest_block_header.clone();
+        if head.state_root.is_zero() {
+            head.state_root = H256::from_slice(&state.tree_hash_root());
+        }
+        let head_root = H256::from_slice(&head.signed_root());
+        let target_root = if epoch.start_slot::<C>() == state.slot {
+            head_root
+        } else {
+            state.get_block_root(epoch)?
+        };
+
+        let parent_crosslink = &state.current_crosslinks[shard as usize];
+        let data = AttestationData {
+            beacon_block_root: head_root,
+            source: state.current_justified_checkpoint.clone(),
+            target: Checkpoint {
+                epoch,
+                root: target_root,
+            },
+            crosslink: Crosslink {
+                shard,
+                parent_root: H256::from_slice(&parent_crosslink.tree_hash_root()),
+                start_epoch: parent_crosslink.end_epoch,
+                end_epoch: core::cmp::min(
+                    epoch,
+                    parent_crosslink.end_epoch + C::max_epochs_per_crosslink(),
+                ),
+                data_root: H256::zero(),
+            },
+        };
+
+        let bits =
+            || BitList::with_capacity(committee.len()).expect("committee fits in the bitfield");
+        let attestation = Attestation {
+            aggregation_bits: bits(),
+            data,
+            custody_bits: bits(),
+            signature: Signature::empty_signature(),
+        };
+
+        Ok(Self {
+            committee,
+            attestation,
+        })
+    }
+
+    pub fn committee(&self) -> &[ValidatorIndex] {
+        &self.committee
+    }
+
+    /// Marks every committee member as an attester and aggregates their signatures.
+    ///
+    /// `keypairs` is indexed by validator index, as returned by `TestingBeaconStateBuilder`.
+    pub fn sign(&mut self, keypairs: &[Keypair], state: &BeaconState<C>) -> &mut Self {
+        for position in 0..self.committee.len() {
+            let _ = self.attestation.aggregation_bits.set(position, true);
+        }
+
+        let mut indices = self.committee.clone();
+        indices.sort();
+        let secret_keys = indices
+            .iter()
+            .map(|index| &keypairs[index.as_usize()].sk)
+            .collect::<Vec<_>>();
+        let mut indexed_attestation = IndexedAttestation::<C> {
+            custody_bit_0_indices: VariableList::from(indices),
+            custody_bit_1_indices: VariableList::default(),
+            data: self.attestation.data.clone(),
+            signature: Signature::empty_signature(),
+        };
+        indexed_attestation.sign(&secret_keys, state);
+        self.attestation.signature = indexed_attestation.signature;
+
+        self
+    }
+
+    pub fn build(self) -> Attestation<C> {
+        self.attestation
+    }
+}
+
+/// Returns a state with `validator_count` active validators holding the maximum effective balance.
+///
+/// Unlike `TestingBeaconStateBuilder`, the validators share the default public key and the state
+/// has no genesis values, which is enough for tests of single processing steps.
+pub fn state_with_validators<C: Config>(validator_count: usize) -> BeaconState<C> {
+    let mut state = BeaconState::default();
+    for _ in 0..validator_count {
+        let validator = Validator {
+            pubkey: PublicKey::default(),
+            withdrawal_credentials: H256::zero(),
+            effective_balance: C::max_effective_balance(),
+            slashed: false,
+            activation_eligibility_epoch: Epoch::new(0),
+            activation_epoch: Epoch::new(0),
+            exit_epoch: consts::FAR_FUTURE_EPOCH,
+            withdrawable_epoch: consts::FAR_FUTURE_EPOCH,
+        };
+        // Both lists are bounded by `ValidatorRegistryLimit`, which no fixture reaches.
+        let _ = state.validators.push(validator);
+        let _ = state.balances.push(C::max_effective_balance());
+    }
+    state
+}
+
+/// Returns the first shard that attests in the state's current slot.
+pub fn first_shard_of_slot<C: Config>(
+    state: &BeaconState<C>,
+) -> Result<Shard, beacon_state::Error> {
+    let epoch = state.get_current_epoch();
+    let committees_per_slot =
+        committees::get_committee_count(state, epoch) / C::SlotsPerEpoch::to_u64();
+    let offset = committees_per_slot * (state.slot % C::SlotsPerEpoch::to_u64()).as_u64();
+
+    Ok((committees::get_start_shard(state, epoch)? + offset) % C::ShardCount::to_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MinimalConfig;
+    use crate::genesis::is_valid_genesis_state;
+    use crate::state_transition::{process_slots, state_transition};
+
+    #[test]
+    fn keypairs_match_interop_secret_keys() {
+        let expected = [
+            "25295f0d1d592a90b333e26e85149708208e9f8e8bc18f6c77bd62f8ad7a6866",
+            "51d0b65185db6989ab0b560d6deed19c7ead0e24b9b6372cbecb1f26bdfad000",
+            "315ed405fafe339603932eebe8dbfd650ce5dafa561f6928664c75db85f97857",
+        ];
+
+        for (keypair, expected) in generate_deterministic_keypairs(3).iter().zip(&expected) {
+            let secret_key_bytes = keypair.sk.as_ssz_bytes();
+            let scalar_hex = secret_key_bytes[16..]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>();
+            assert_eq!(secret_key_bytes[..16], [0; 16]);
+            assert_eq!(scalar_hex, *expected);
+        }
+    }
+
+    #[test]
+    fn state_builder_activates_every_validator() {
+        let (state, keypairs) =
+            TestingBeaconStateBuilder::<MinimalConfig>::from_deterministic_keypairs(64).build();
+
+        assert_eq!(keypairs.len(), 64);
+        assert_eq!(state.validators.len(), 64);
+        assert!(state
+            .validators
+            .iter()
+            .zip(&keypairs)
+            .all(|(validator, keypair)| validator.pubkey == keypair.pk));
+        assert_eq!(
+            state
+                .get_active_validator_indices(MinimalConfig::genesis_epoch())
+                .len(),
+            64
+        );
+        assert!(is_valid_genesis_state(&state));
+    }
+
+    #[test]
+    fn built_block_with_attestation_passes_state_transition() {
+        let (mut state, keypairs) =
+            TestingBeaconStateBuilder::<MinimalConfig>::from_deterministic_keypairs(64).build();
+
+        let mut attestation_builder =
+            TestingAttestationBuilder::new(&state, first_shard_of_slot(&state).expect("shard"))
+                .expect("committee exists");
+        attestation_builder.sign(&keypairs, &state);
+        let attestation = attestation_builder.build();
+
+        process_slots(&mut state, Slot::new(1)).expect("slots should be processed");
+        let proposer_index =
+            committees::get_beacon_proposer_index(&state).expect("proposer exists");
+        let mut block_builder = TestingBeaconBlockBuilder::new(&state);
+        block_builder.insert_attestation(attestation);
+        let block = block_builder
+            .build(&keypairs[proposer_index.as_usize()].sk, &state)
+            .expect("block should be valid");
+
+        state_transition(&mut state, &block, true).expect("state transition should succeed");
+        assert_eq!(state.current_epoch_attestations.len(), 1);
+    }
+}
@@ -2,6 +2,8 @@
 use bls::PublicKeyBytes;
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
+#[cfg(feature = "ssz_new")]
+use ssz_new::{SszDeserialize, SszSerialize, SszType, SszView};
 use ssz_types::{BitList, FixedVector, VariableList};
 use tree_hash::TreeHash;
 use tree_hash_derive::{SignedRoot, TreeHash};
@@ -10,18 +12,30 @@ use typenum::{Sum, U1};
 use crate::config::*;
 use crate::consts;
 use crate::primitives::*;
+use crate::serde_utils;
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Attestation<C: Config> {
     pub aggregation_bits: BitList<C::MaxValidatorsPerCommittee>,
     pub data: AttestationData,
     pub custody_bits: BitList<C::MaxValidatorsPerCommittee>,
+    #[signed_root(skip_hashing)]
     pub signature: Signature,
 }
 
 #[derive(
     Clone, PartialEq, Eq, Debug, Hash, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct AttestationData {
     pub beacon_block_root: H256,
     pub source: Checkpoint,
@@ -29,9 +43,24 @@ pub struct AttestationData {
     pub crosslink: Crosslink,
 }
 
+impl AttestationData {
+    pub fn is_slashable_attestation_data(&self, other: &Self) -> bool {
+        let double_vote = self != other && self.target.epoch == other.target.epoch;
+        let surround_vote =
+            self.source.epoch < other.source.epoch && other.target.epoch < self.target.epoch;
+
+        double_vote || surround_vote
+    }
+}
+
 #[derive(
     Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct AttestationDataAndCustodyBit {
     pub data: AttestationData,
     pub custody_bit: bool,
@@ -51,6 +80,11 @@ pub struct AttestationDataAndCustodyBit {
     TreeHash,
     SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct AttestationDuty {
     pub slot: Slot,
     pub shard: Shard,
@@ -59,12 +93,22 @@ pub struct AttestationDuty {
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct AttesterSlashing<C: Config> {
     pub attestation_1: IndexedAttestation<C>,
     pub attestation_2: IndexedAttestation<C>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct BeaconBlock<C: Config> {
     pub slot: Slot,
     pub parent_root: H256,
@@ -75,9 +119,18 @@ pub struct BeaconBlock<C: Config> {
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct BeaconBlockBody<C: Config> {
     pub randao_reveal: Signature,
     pub eth1_data: Eth1Data,
+    #[serde(
+        deserialize_with = "serde_utils::graffiti_from_hex_str",
+        serialize_with = "serde_utils::graffiti_to_hex_str"
+    )]
     pub graffiti: [u8; 32],
     pub proposer_slashings: VariableList<ProposerSlashing, C::MaxProposerSlashings>,
     pub attester_slashings: VariableList<AttesterSlashing<C>, C::MaxAttesterSlashings>,
@@ -87,28 +140,83 @@ pub struct BeaconBlockBody<C: Config> {
     pub transfers: VariableList<Transfer, C::MaxTransfers>,
 }
 
+impl<C: Config> Default for BeaconBlockBody<C> {
+    fn default() -> Self {
+        Self {
+            randao_reveal: Signature::empty_signature(),
+            eth1_data: Eth1Data::default(),
+            graffiti: [0; 32],
+            proposer_slashings: VariableList::default(),
+            attester_slashings: VariableList::default(),
+            attestations: VariableList::default(),
+            deposits: VariableList::default(),
+            voluntary_exits: VariableList::default(),
+            transfers: VariableList::default(),
+        }
+    }
+}
+
 #[derive(
     Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct BeaconBlockHeader {
     pub slot: Slot,
     pub parent_root: H256,
     pub state_root: H256,
     pub body_root: H256,
+    #[signed_root(skip_hashing)]
     pub signature: Signature,
 }
 
+impl Default for BeaconBlockHeader {
+    fn default() -> Self {
+        Self {
+            slot: Slot::default(),
+            parent_root: H256::zero(),
+            state_root: H256::zero(),
+            body_root: H256::zero(),
+            signature: Signature::empty_signature(),
+        }
+    }
+}
+
 #[derive(
     Clone, PartialEq, Eq, Debug, Default, Hash, Deserialize, Serialize, Encode, Decode, TreeHash,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Checkpoint {
     pub epoch: Epoch,
     pub root: H256,
 }
 
+#[derive(Clone, PartialEq, Debug, Default, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
+pub struct CompactCommittee<C: Config> {
+    pub pubkeys: VariableList<PublicKey, C::MaxValidatorsPerCommittee>,
+    pub compact_validators: VariableList<u64, C::MaxValidatorsPerCommittee>,
+}
+
 #[derive(
     Clone, PartialEq, Eq, Debug, Default, Hash, Deserialize, Serialize, Encode, Decode, TreeHash,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Crosslink {
     pub shard: u64,
     pub parent_root: H256,
@@ -118,6 +226,11 @@ pub struct Crosslink {
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Deposit {
     pub proof: FixedVector<H256, Sum<consts::DepositContractTreeDepth, U1>>,
     pub data: DepositData,
@@ -126,15 +239,27 @@ pub struct Deposit {
 #[derive(
     Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct DepositData {
     pub pubkey: PublicKeyBytes,
     pub withdrawal_credentials: H256,
-    pub amount: u64,
+    pub amount: Gwei,
     #[signed_root(skip_hashing)]
     pub signature: Signature,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[derive(
+    Clone, PartialEq, Eq, Debug, Default, Deserialize, Serialize, Encode, Decode, TreeHash,
+)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Eth1Data {
     pub deposit_root: H256,
     pub deposit_count: u64,
@@ -142,40 +267,83 @@ pub struct Eth1Data {
 }
 
 #[derive(
-    Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    Encode,
+    Decode,
+    TreeHash,
+    SignedRoot,
+)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
 )]
 pub struct Fork {
+    #[serde(
+        deserialize_with = "serde_utils::fork_version_from_hex_str",
+        serialize_with = "serde_utils::fork_version_to_hex_str"
+    )]
     pub previous_version: Version,
+    #[serde(
+        deserialize_with = "serde_utils::fork_version_from_hex_str",
+        serialize_with = "serde_utils::fork_version_to_hex_str"
+    )]
     pub current_version: Version,
     pub epoch: Epoch,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct HistoricalBatch<C: Config> {
     pub block_roots: FixedVector<H256, C::SlotsPerHistoricalRoot>,
     pub state_roots: FixedVector<H256, C::SlotsPerHistoricalRoot>,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct IndexedAttestation<C: Config> {
-    pub custody_bit_0_indices: VariableList<u64, C::MaxValidatorsPerCommittee>,
-    pub custody_bit_1_indices: VariableList<u64, C::MaxValidatorsPerCommittee>,
+    pub custody_bit_0_indices: VariableList<ValidatorIndex, C::MaxValidatorsPerCommittee>,
+    pub custody_bit_1_indices: VariableList<ValidatorIndex, C::MaxValidatorsPerCommittee>,
     pub data: AttestationData,
     #[signed_root(skip_hashing)]
     pub signature: Signature,
 }
 
 #[derive(Clone, PartialEq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct PendingAttestation<C: Config> {
     pub aggregation_bits: BitList<C::MaxValidatorsPerCommittee>,
     pub data: AttestationData,
-    pub inclusion_delay: u64,
-    pub proposer_index: u64,
+    pub inclusion_delay: Slot,
+    pub proposer_index: ValidatorIndex,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct ProposerSlashing {
-    pub proposer_index: u64,
+    pub proposer_index: ValidatorIndex,
     pub header_1: BeaconBlockHeader,
     pub header_2: BeaconBlockHeader,
 }
@@ -183,11 +351,16 @@ pub struct ProposerSlashing {
 #[derive(
     Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Transfer {
-    pub sender: u64,
-    pub recipient: u64,
-    pub amount: u64,
-    pub fee: u64,
+    pub sender: ValidatorIndex,
+    pub recipient: ValidatorIndex,
+    pub amount: Gwei,
+    pub fee: Gwei,
     pub slot: Slot,
     pub pubkey: PublicKey,
     #[signed_root(skip_hashing)]
@@ -195,10 +368,15 @@ pub struct Transfer {
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash)]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct Validator {
     pub pubkey: PublicKey,
     pub withdrawal_credentials: H256,
-    pub effective_balance: u64,
+    pub effective_balance: Gwei,
     pub slashed: bool,
     pub activation_eligibility_epoch: Epoch,
     pub activation_epoch: Epoch,
@@ -206,12 +384,27 @@ pub struct Validator {
     pub withdrawable_epoch: Epoch,
 }
 
+impl Validator {
+    pub fn is_active_validator(&self, epoch: Epoch) -> bool {
+        self.activation_epoch <= epoch && epoch < self.exit_epoch
+    }
+
+    pub fn is_slashable_validator(&self, epoch: Epoch) -> bool {
+        !self.slashed && self.activation_epoch <= epoch && epoch < self.withdrawable_epoch
+    }
+}
+
 #[derive(
     Clone, PartialEq, Eq, Debug, Deserialize, Serialize, Encode, Decode, TreeHash, SignedRoot,
 )]
+#[cfg_attr(
+    feature = "ssz_new",
+    derive(SszType, SszSerialize, SszDeserialize, SszView),
+    ssz(crate = "ssz_new")
+)]
 pub struct VoluntaryExit {
     pub epoch: Epoch,
-    pub validator_index: u64,
+    pub validator_index: ValidatorIndex,
     #[signed_root(skip_hashing)]
     pub signature: Signature,
 }
@@ -0,0 +1,339 @@
+//! Runs the consensus spec tests against the `types` containers and state transition.
+//!
+//! The fixtures are not vendored. Point `ETH2_SPEC_TESTS` at the `tests` directory of an
+//! `eth2.0-spec-tests` release matching the implemented spec version (v0.8), e.g.
+//! `ETH2_SPEC_TESTS=~/eth2.0-spec-tests/tests cargo test -p types --test spec_tests -- --ignored`.
+//! The tests are ignored by default, and fail if the variable is unset or a handler has no cases.
+//!
+//! The containers are encoded and decoded with the backend picked by the `ssz_new` feature.
+//!
+//! Cases are read from `<config>/phase0/<runner>/<handler>/<suite>/<case>`.
+
+use std::env;
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tree_hash::{SignedRoot, TreeHash};
+
+use types::beacon_state::BeaconState;
+use types::block_processing;
+use types::codec::{self, Decode, Encode};
+use types::config::{Config, MainnetConfig, MinimalConfig};
+use types::epoch_processing;
+use types::primitives::H256;
+use types::types::*;
+
+const SPEC_TESTS_ENV: &str = "ETH2_SPEC_TESTS";
+
+fn case_directories(config_name: &str, runner: &str, handler: &str) -> Vec<PathBuf> {
+    let root = env::var_os(SPEC_TESTS_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| panic!("{} is not set", SPEC_TESTS_ENV));
+    let handler_directory = root
+        .join(config_name)
+        .join("phase0")
+        .join(runner)
+        .join(handler);
+
+    let mut cases = subdirectories(&handler_directory)
+        .iter()
+        .flat_map(|suite| subdirectories(suite))
+        .collect::<Vec<_>>();
+    // A wrong path or handler name would otherwise pass without running anything.
+    assert!(
+        !cases.is_empty(),
+        "{}: no cases found",
+        handler_directory.display()
+    );
+    cases.sort();
+    cases
+}
+
+fn subdirectories(directory: &Path) -> Vec<PathBuf> {
+    fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn read_ssz<T: Decode>(path: &Path) -> T {
+    let bytes = fs::read(path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    codec::decode(&bytes).unwrap_or_else(|error| panic!("{}: {:?}", path.display(), error))
+}
+
+fn read_optional_ssz<T: Decode>(path: &Path) -> Option<T> {
+    if path.exists() {
+        Some(read_ssz(path))
+    } else {
+        None
+    }
+}
+
+fn read_yaml<T: DeserializeOwned>(path: &Path) -> T {
+    let yaml =
+        fs::read_to_string(path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    serde_yaml::from_str(&yaml).unwrap_or_else(|error| panic!("{}: {}", path.display(), error))
+}
+
+#[derive(Deserialize)]
+struct Roots {
+    root: H256,
+    signing_root: Option<H256>,
+}
+
+#[derive(Deserialize, Default)]
+struct Meta {
+    #[serde(default)]
+    bls_setting: u8,
+}
+
+// `bls_setting` 2 marks cases that are only valid with signature verification turned off.
+fn validate_signatures(case: &Path) -> bool {
+    let meta_path = case.join("meta.yaml");
+    let meta = if meta_path.exists() {
+        read_yaml::<Meta>(&meta_path)
+    } else {
+        Meta::default()
+    };
+
+    meta.bls_setting != 2
+}
+
+// ssz_static
+
+/// Checks that `serialized.ssz` decodes to `value.yaml`, encodes back to the same bytes and hashes
+/// to `roots.yaml`.
+fn ssz_static<T>(config_name: &str, container: &str) -> Vec<(PathBuf, T, Roots)>
+where
+    T: Encode + Decode + TreeHash + DeserializeOwned + PartialEq + Debug,
+{
+    case_directories(config_name, "ssz_static", container)
+        .into_iter()
+        .map(|case| {
+            let bytes = fs::read(case.join("serialized.ssz")).expect("serialized.ssz is readable");
+            let decoded = codec::decode::<T>(&bytes)
+                .unwrap_or_else(|error| panic!("{}: {:?}", case.display(), error));
+            let value = read_yaml::<T>(&case.join("value.yaml"));
+            let roots = read_yaml::<Roots>(&case.join("roots.yaml"));
+
+            assert_eq!(decoded, value, "{}", case.display());
+            let encoded = codec::encode(&value)
+                .unwrap_or_else(|error| panic!("{}: {:?}", case.display(), error));
+            assert_eq!(encoded, bytes, "{}", case.display());
+            assert_eq!(
+                H256::from_slice(&value.tree_hash_root()),
+                roots.root,
+                "{}",
+                case.display()
+            );
+
+            (case, value, roots)
+        })
+        .collect()
+}
+
+/// Like `ssz_static`, additionally checking the `signing_root` of containers with a signature.
+fn ssz_static_signed<T>(config_name: &str, container: &str)
+where
+    T: Encode + Decode + TreeHash + SignedRoot + DeserializeOwned + PartialEq + Debug,
+{
+    for (case, value, roots) in ssz_static::<T>(config_name, container) {
+        let signing_root = roots
+            .signing_root
+            .unwrap_or_else(|| panic!("{}: signing_root is missing", case.display()));
+        assert_eq!(
+            H256::from_slice(&value.signed_root()),
+            signing_root,
+            "{}",
+            case.display()
+        );
+    }
+}
+
+fn run_ssz_static<C: Config + DeserializeOwned>(config_name: &str) {
+    ssz_static_signed::<Attestation<C>>(config_name, "Attestation");
+    ssz_static::<AttestationData>(config_name, "AttestationData");
+    ssz_static::<AttestationDataAndCustodyBit>(config_name, "AttestationDataAndCustodyBit");
+    ssz_static::<AttesterSlashing<C>>(config_name, "AttesterSlashing");
+    ssz_static_signed::<BeaconBlock<C>>(config_name, "BeaconBlock");
+    ssz_static::<BeaconBlockBody<C>>(config_name, "BeaconBlockBody");
+    ssz_static_signed::<BeaconBlockHeader>(config_name, "BeaconBlockHeader");
+    ssz_static::<BeaconState<C>>(config_name, "BeaconState");
+    ssz_static::<Checkpoint>(config_name, "Checkpoint");
+    ssz_static::<CompactCommittee<C>>(config_name, "CompactCommittee");
+    ssz_static::<Crosslink>(config_name, "Crosslink");
+    ssz_static::<Deposit>(config_name, "Deposit");
+    ssz_static_signed::<DepositData>(config_name, "DepositData");
+    ssz_static::<Eth1Data>(config_name, "Eth1Data");
+    ssz_static::<Fork>(config_name, "Fork");
+    ssz_static::<HistoricalBatch<C>>(config_name, "HistoricalBatch");
+    ssz_static_signed::<IndexedAttestation<C>>(config_name, "IndexedAttestation");
+    ssz_static::<PendingAttestation<C>>(config_name, "PendingAttestation");
+    ssz_static::<ProposerSlashing>(config_name, "ProposerSlashing");
+    ssz_static_signed::<Transfer>(config_name, "Transfer");
+    ssz_static::<Validator>(config_name, "Validator");
+    ssz_static_signed::<VoluntaryExit>(config_name, "VoluntaryExit");
+}
+
+#[test]
+#[ignore]
+fn ssz_static_minimal() {
+    run_ssz_static::<MinimalConfig>("minimal");
+}
+
+#[test]
+#[ignore]
+fn ssz_static_mainnet() {
+    run_ssz_static::<MainnetConfig>("mainnet");
+}
+
+// operations
+
+/// Applies the operation in `<operation_file>.ssz` to `pre.ssz` and compares the result with
+/// `post.ssz`. Cases without `post.ssz` must be rejected.
+fn operations<C, T, F>(config_name: &str, handler: &str, operation_file: &str, process: F)
+where
+    C: Config,
+    T: Decode,
+    F: Fn(&mut BeaconState<C>, &T, bool) -> Result<(), block_processing::Error>,
+{
+    for case in case_directories(config_name, "operations", handler) {
+        let mut state = read_ssz::<BeaconState<C>>(&case.join("pre.ssz"));
+        let operation = read_ssz::<T>(&case.join(format!("{}.ssz", operation_file)));
+        let expected = read_optional_ssz::<BeaconState<C>>(&case.join("post.ssz"));
+
+        let result = process(&mut state, &operation, validate_signatures(&case));
+        match expected {
+            Some(expected) => {
+                assert_eq!(result, Ok(()), "{}", case.display());
+                assert_eq!(state, expected, "{}", case.display());
+            }
+            None => assert!(result.is_err(), "{}: should be invalid", case.display()),
+        }
+    }
+}
+
+fn run_operations<C: Config>(config_name: &str) {
+    operations::<C, Attestation<C>, _>(
+        config_name,
+        "attestation",
+        "attestation",
+        block_processing::process_attestation,
+    );
+    operations::<C, AttesterSlashing<C>, _>(
+        config_name,
+        "attester_slashing",
+        "attester_slashing",
+        block_processing::process_attester_slashing,
+    );
+    operations::<C, BeaconBlock<C>, _>(
+        config_name,
+        "block_header",
+        "block",
+        block_processing::process_block_header,
+    );
+    operations::<C, Deposit, _>(config_name, "deposit", "deposit", |state, deposit, _| {
+        block_processing::process_deposit(state, deposit)
+    });
+    operations::<C, ProposerSlashing, _>(
+        config_name,
+        "proposer_slashing",
+        "proposer_slashing",
+        block_processing::process_proposer_slashing,
+    );
+    operations::<C, Transfer, _>(
+        config_name,
+        "transfer",
+        "transfer",
+        block_processing::process_transfer,
+    );
+    operations::<C, VoluntaryExit, _>(
+        config_name,
+        "voluntary_exit",
+        "voluntary_exit",
+        block_processing::process_voluntary_exit,
+    );
+}
+
+#[test]
+#[ignore]
+fn operations_minimal() {
+    run_operations::<MinimalConfig>("minimal");
+}
+
+#[test]
+#[ignore]
+fn operations_mainnet() {
+    run_operations::<MainnetConfig>("mainnet");
+}
+
+// epoch_processing
+
+/// Runs one epoch processing step on `pre.ssz` and compares the result with `post.ssz`.
+fn epoch_processing<C, F>(config_name: &str, handler: &str, process: F)
+where
+    C: Config,
+    F: Fn(&mut BeaconState<C>) -> Result<(), epoch_processing::Error>,
+{
+    for case in case_directories(config_name, "epoch_processing", handler) {
+        let mut state = read_ssz::<BeaconState<C>>(&case.join("pre.ssz"));
+        let expected = read_optional_ssz::<BeaconState<C>>(&case.join("post.ssz"));
+
+        let result = process(&mut state);
+        match expected {
+            Some(expected) => {
+                assert_eq!(result, Ok(()), "{}", case.display());
+                assert_eq!(state, expected, "{}", case.display());
+            }
+            None => assert!(result.is_err(), "{}: should be invalid", case.display()),
+        }
+    }
+}
+
+fn run_epoch_processing<C: Config>(config_name: &str) {
+    epoch_processing::<C, _>(
+        config_name,
+        "justification_and_finalization",
+        epoch_processing::process_justification_and_finalization,
+    );
+    epoch_processing::<C, _>(
+        config_name,
+        "crosslinks",
+        epoch_processing::process_crosslinks,
+    );
+    epoch_processing::<C, _>(
+        config_name,
+        "registry_updates",
+        epoch_processing::process_registry_updates,
+    );
+    epoch_processing::<C, _>(
+        config_name,
+        "slashings",
+        epoch_processing::process_slashings,
+    );
+    epoch_processing::<C, _>(
+        config_name,
+        "final_updates",
+        epoch_processing::process_final_updates,
+    );
+}
+
+#[test]
+#[ignore]
+fn epoch_processing_minimal() {
+    run_epoch_processing::<MinimalConfig>("minimal");
+}
+
+#[test]
+#[ignore]
+fn epoch_processing_mainnet() {
+    run_epoch_processing::<MainnetConfig>("mainnet");
+}
@@ -0,0 +1,214 @@
+//! Checks that the `ssz_new` derives encode every container byte for byte like the legacy
+//! `eth2_ssz` ones and decode those bytes back to the same value.
+//!
+//! Only built with the backend enabled: `cargo test -p types --features ssz_new --test ssz_backends`.
+
+#![cfg(feature = "ssz_new")]
+
+use std::fmt::Debug;
+
+use ssz::Encode;
+use ssz_new_types::{BitList, FixedVector, VariableList};
+use tree_hash::SignedRoot;
+use typenum::Unsigned;
+
+use types::beacon_state::{BeaconState, BeaconStateView};
+use types::committees;
+use types::config::{Config, MainnetConfig, MinimalConfig};
+use types::primitives::*;
+use types::state_transition::{process_slots, state_transition};
+use types::test_utils::*;
+use types::types::*;
+
+fn assert_backends_agree<T>(value: &T)
+where
+    T: Encode + ssz_new::Serialize + ssz_new::Deserialize + PartialEq + Debug,
+{
+    let legacy = value.as_ssz_bytes();
+    let new = ssz_new::Serialize::serialize(value).expect("value should serialize");
+    assert_eq!(new, legacy, "{:?}", value);
+    assert_eq!(ssz_new::Serialize::serialized_len(value), legacy.len());
+
+    let decoded = <T as ssz_new::Deserialize>::deserialize(&legacy).expect("bytes should decode");
+    assert_eq!(&decoded, value);
+}
+
+fn signature(message: &[u8], keypair: &Keypair) -> Signature {
+    Signature::new(message, 0, &keypair.sk)
+}
+
+fn run_all_containers<C: Config>() {
+    // One validator per slot, so every slot has a committee and a proposer.
+    let validator_count = C::SlotsPerEpoch::to_usize();
+    let (mut state, keypairs) =
+        TestingBeaconStateBuilder::<C>::from_deterministic_keypairs(validator_count).build();
+
+    let mut attestation_builder =
+        TestingAttestationBuilder::new(&state, first_shard_of_slot(&state).expect("shard"))
+            .expect("committee exists");
+    attestation_builder.sign(&keypairs, &state);
+    let attestation = attestation_builder.build();
+
+    process_slots(&mut state, Slot::new(1)).expect("slots should be processed");
+    let proposer_index = committees::get_beacon_proposer_index(&state).expect("proposer exists");
+    let mut block_builder = TestingBeaconBlockBuilder::new(&state);
+    block_builder.insert_attestation(attestation.clone());
+    let block = block_builder
+        .build(&keypairs[proposer_index.as_usize()].sk, &state)
+        .expect("block should be valid");
+    state_transition(&mut state, &block, true).expect("state transition should succeed");
+
+    let header = BeaconBlockHeader {
+        slot: block.slot,
+        parent_root: block.parent_root,
+        state_root: block.state_root,
+        body_root: H256::repeat_byte(1),
+        signature: signature(&block.signed_root(), &keypairs[0]),
+    };
+    let indexed_attestation = IndexedAttestation::<C> {
+        custody_bit_0_indices: VariableList::from(vec![ValidatorIndex::new(3)]),
+        custody_bit_1_indices: VariableList::from(vec![ValidatorIndex::new(1)]),
+        data: attestation.data.clone(),
+        signature: attestation.signature.clone(),
+    };
+    let deposit_data = DepositData {
+        pubkey: keypairs[1].pk.clone().into(),
+        withdrawal_credentials: H256::repeat_byte(2),
+        amount: C::max_effective_balance(),
+        signature: signature(&[1], &keypairs[1]),
+    };
+    let mut aggregation_bits = BitList::with_capacity(5).expect("capacity is in range");
+    aggregation_bits.set(4, true).expect("bit is in range");
+
+    assert_backends_agree(&state);
+    assert_backends_agree(&block);
+    assert_backends_agree(&block.body);
+    assert_backends_agree(&header);
+    assert_backends_agree(&attestation);
+    assert_backends_agree(&attestation.data);
+    assert_backends_agree(&AttestationDataAndCustodyBit {
+        data: attestation.data.clone(),
+        custody_bit: true,
+    });
+    assert_backends_agree(&AttestationDuty {
+        slot: Slot::new(9),
+        shard: 2,
+        committee_index: 3,
+        committee_len: 4,
+    });
+    assert_backends_agree(&AttesterSlashing {
+        attestation_1: indexed_attestation.clone(),
+        attestation_2: IndexedAttestation {
+            custody_bit_0_indices: VariableList::default(),
+            ..indexed_attestation.clone()
+        },
+    });
+    assert_backends_agree(&indexed_attestation);
+    assert_backends_agree(&PendingAttestation::<C> {
+        aggregation_bits,
+        data: attestation.data.clone(),
+        inclusion_delay: Slot::new(1),
+        proposer_index: ValidatorIndex::new(5),
+    });
+    assert_backends_agree(&state.current_justified_checkpoint);
+    assert_backends_agree(&CompactCommittee::<C> {
+        pubkeys: VariableList::from(vec![keypairs[0].pk.clone(), keypairs[1].pk.clone()]),
+        compact_validators: VariableList::from(vec![1, 2, 3]),
+    });
+    assert_backends_agree(&state.current_crosslinks[0]);
+    assert_backends_agree(&Deposit {
+        proof: FixedVector::from(vec![H256::repeat_byte(3); 2]),
+        data: deposit_data.clone(),
+    });
+    assert_backends_agree(&deposit_data);
+    assert_backends_agree(&state.eth1_data);
+    assert_backends_agree(&Fork {
+        previous_version: [1, 2, 3, 4],
+        current_version: [5, 6, 7, 8],
+        epoch: Epoch::new(9),
+    });
+    assert_backends_agree(&HistoricalBatch::<C> {
+        block_roots: state.block_roots.clone(),
+        state_roots: state.state_roots.clone(),
+    });
+    assert_backends_agree(&ProposerSlashing {
+        proposer_index: ValidatorIndex::new(2),
+        header_1: header.clone(),
+        header_2: BeaconBlockHeader {
+            body_root: H256::repeat_byte(4),
+            ..header.clone()
+        },
+    });
+    assert_backends_agree(&Transfer {
+        sender: ValidatorIndex::new(1),
+        recipient: ValidatorIndex::new(2),
+        amount: Gwei::new(3),
+        fee: Gwei::new(4),
+        slot: Slot::new(5),
+        pubkey: keypairs[2].pk.clone(),
+        signature: signature(&[2], &keypairs[2]),
+    });
+    assert_backends_agree(&state.validators[0]);
+    assert_backends_agree(&VoluntaryExit {
+        epoch: Epoch::new(6),
+        validator_index: ValidatorIndex::new(7),
+        signature: signature(&[3], &keypairs[3]),
+    });
+}
+
+#[test]
+fn all_containers_minimal() {
+    run_all_containers::<MinimalConfig>();
+}
+
+#[test]
+fn all_containers_mainnet() {
+    run_all_containers::<MainnetConfig>();
+}
+
+#[test]
+fn skipped_fields_are_not_encoded() {
+    let (mut state, _) =
+        TestingBeaconStateBuilder::<MinimalConfig>::from_deterministic_keypairs(16).build();
+    let bytes = state.as_ssz_bytes();
+
+    state
+        .build_committee_cache(state.get_current_epoch())
+        .expect("committee cache should build");
+    assert_eq!(ssz_new::Serialize::serialize(&state).expect("state"), bytes);
+
+    let decoded =
+        <BeaconState<MinimalConfig> as ssz_new::Deserialize>::deserialize(&bytes).expect("state");
+    assert!(decoded
+        .committee_cache(decoded.get_current_epoch())
+        .is_none());
+}
+
+#[test]
+fn views_read_fields_without_decoding() {
+    let (state, _) =
+        TestingBeaconStateBuilder::<MinimalConfig>::from_deterministic_keypairs(16).build();
+    let bytes = state.as_ssz_bytes();
+
+    let view = BeaconStateView::<MinimalConfig>::new(&bytes).expect("state bytes are valid");
+    assert_eq!(view.slot().decode().expect("slot"), state.slot);
+    assert_eq!(view.fork().decode().expect("fork"), state.fork);
+    assert_eq!(view.validators().len().expect("validators"), 16);
+    assert_eq!(
+        view.validators().get(3).expect("validator"),
+        Some(state.validators[3].clone())
+    );
+    assert_eq!(view.validators().get(16).expect("validator"), None);
+    assert_eq!(
+        view.block_roots().get(0).expect("block root"),
+        Some(state.block_roots[0])
+    );
+
+    let eth1_data = Eth1DataView::new(view.eth1_data().as_bytes()).expect("eth1 data");
+    assert_eq!(
+        eth1_data.deposit_count().decode().expect("deposit count"),
+        state.eth1_data.deposit_count
+    );
+
+    assert!(BeaconStateView::<MinimalConfig>::new(&bytes[..100]).is_err());
+}
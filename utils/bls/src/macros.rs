@@ -38,6 +38,45 @@ macro_rules! impl_ssz {
                 }
             }
         }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::SszType for $type {
+            fn is_variable_size() -> bool {
+                false
+            }
+
+            fn fixed_length() -> usize {
+                $byte_size
+            }
+        }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::Serialize for $type {
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), ssz_new::Error> {
+                buffer.extend_from_slice(&self.as_bytes());
+                Ok(())
+            }
+
+            fn serialized_len(&self) -> usize {
+                $byte_size
+            }
+        }
+
+        #[cfg(feature = "ssz_new")]
+        impl ssz_new::Deserialize for $type {
+            fn deserialize(bytes: &[u8]) -> Result<Self, ssz_new::Error> {
+                let got = bytes.len();
+                let required = <Self as ssz_new::SszType>::fixed_length();
+
+                if got != required {
+                    Err(ssz_new::Error::InvalidByteLength { required, got })
+                } else {
+                    $type::from_bytes(bytes).map_err(|e| {
+                        ssz_new::Error::InvalidBytes(format!("Invalid {}: {:?}", $item_str, e))
+                    })
+                }
+            }
+        }
     };
 }
 
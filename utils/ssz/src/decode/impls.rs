@@ -410,6 +410,13 @@ pub fn decode_list_of_variable_length_items<T: Decode>(
         });
     }
 
+    // The offsets must fit in the bytes, which also bounds the allocation below.
+    if next_variable_byte > bytes.len() {
+        return Err(DecodeError::OutOfBoundsByte {
+            i: next_variable_byte,
+        });
+    }
+
     let mut values = Vec::with_capacity(num_items);
     for i in 1..=num_items {
         let slice_option = if i == num_items {
@@ -558,6 +565,10 @@ mod tests {
             <Vec<Vec<u16>>>::from_ssz_bytes(&[8, 0, 0, 0, 9, 0, 0, 0]),
             Err(DecodeError::OutOfBoundsByte { i: 9 })
         );
+        assert_eq!(
+            <Vec<Vec<u16>>>::from_ssz_bytes(&[252, 255, 255, 255]),
+            Err(DecodeError::OutOfBoundsByte { i: 0xffff_fffc })
+        );
     }
 
     #[test]
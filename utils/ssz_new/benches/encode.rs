@@ -0,0 +1,56 @@
+//! Compares the `ssz_new` encoder with the legacy `eth2_ssz` one on lists shaped like the
+//! validator registry of a state.
+//!
+//! Run with `cargo bench -p ssz_new`.
+
+use criterion::Criterion;
+use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use eth2_ssz::Encode;
+use ssz::Serialize;
+
+fn bench_value<T>(c: &mut Criterion, desc: &str, value: T)
+where
+    T: Encode + Serialize + Clone + 'static,
+{
+    assert_eq!(
+        value.serialize().expect("value should serialize"),
+        value.as_ssz_bytes()
+    );
+
+    let legacy_value = value.clone();
+    let buffer_value = value.clone();
+
+    c.bench(
+        desc,
+        Benchmark::new("eth2_ssz", move |b| {
+            b.iter(|| black_box(legacy_value.as_ssz_bytes()))
+        })
+        .with_function("ssz_new", move |b| b.iter(|| black_box(value.serialize())))
+        .with_function("ssz_new_reused_buffer", move |b| {
+            let mut buffer = Vec::with_capacity(buffer_value.serialized_len());
+            b.iter(|| {
+                buffer.clear();
+                buffer_value
+                    .serialize_into(&mut buffer)
+                    .expect("value should serialize");
+                black_box(buffer.len())
+            })
+        })
+        .sample_size(10),
+    );
+}
+
+fn all_benches(c: &mut Criterion) {
+    let count = 16_384;
+
+    let balances = (0..count as u64).collect::<Vec<_>>();
+    bench_value(c, "balances", balances);
+
+    let public_keys = (0..count)
+        .map(|index| vec![index as u8; 48])
+        .collect::<Vec<_>>();
+    bench_value(c, "public_keys", public_keys);
+}
+
+criterion_group!(benches, all_benches);
+criterion_main!(benches);
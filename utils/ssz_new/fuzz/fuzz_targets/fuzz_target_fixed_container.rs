@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssz_new_fuzz::*;
+
+// Decode a derived fixed size container with both backends.
+fuzz_target!(|data: &[u8]| {
+    assert_backends_agree::<Fixed>(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssz_new_fuzz::*;
+use ssz_types::{typenum::*, FixedVector};
+
+// Decode a vector of fixed size items with both backends.
+fuzz_target!(|data: &[u8]| {
+    assert_backends_agree::<FixedVector<u16, U8>>(data);
+});
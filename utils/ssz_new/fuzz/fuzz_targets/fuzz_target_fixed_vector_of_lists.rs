@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssz_new_fuzz::*;
+use ssz_types::{typenum::*, FixedVector};
+
+// Decode a vector of variable size items with both backends.
+fuzz_target!(|data: &[u8]| {
+    assert_backends_agree::<FixedVector<Vec<u8>, U4>>(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use ethereum_types::H256;
+use libfuzzer_sys::fuzz_target;
+use ssz_new_fuzz::*;
+
+// Decode a H256 with both backends.
+fuzz_target!(|data: &[u8]| {
+    assert_backends_agree::<H256>(data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ssz_new_fuzz::*;
+use ssz_types::{typenum::*, VariableList};
+
+// Decode a bounded list of fixed size items with both backends.
+fuzz_target!(|data: &[u8]| {
+    assert_backends_agree::<VariableList<u64, U16>>(data);
+});
@@ -0,0 +1,68 @@
+//! Differential fuzzing of the legacy `eth2_ssz` against `ssz_new`.
+//!
+//! Every target decodes the fuzzer's bytes as one type with both backends. They must either both
+//! reject the bytes, or both accept them, decode the same value and encode it back to the same
+//! bytes. The collections come from `ssz_new_types`, which implements both codecs like the types
+//! in `types` do.
+
+use std::fmt::Debug;
+
+use ethereum_types::H256;
+use ssz_derive::{Decode, Encode};
+use ssz_new::{SszDeserialize, SszSerialize, SszType};
+use ssz_types::{typenum::*, BitList, FixedVector, VariableList};
+
+/// Panics if the backends disagree on decoding `data` as a `T`.
+pub fn assert_backends_agree<T>(data: &[u8])
+where
+    T: ssz::Decode + ssz::Encode + ssz_new::Deserialize + ssz_new::Serialize + PartialEq + Debug,
+{
+    let legacy = <T as ssz::Decode>::from_ssz_bytes(data);
+    let new = <T as ssz_new::Deserialize>::deserialize(data);
+
+    match (legacy, new) {
+        (Ok(legacy), Ok(new)) => {
+            assert_eq!(legacy, new, "backends decode {:?} differently", data);
+
+            let legacy_bytes = legacy.as_ssz_bytes();
+            let new_bytes = ssz_new::Serialize::serialize(&new).expect("value should serialize");
+            assert_eq!(
+                legacy_bytes, new_bytes,
+                "backends encode {:?} differently",
+                legacy
+            );
+        }
+        (Err(_), Err(_)) => {}
+        (legacy, new) => panic!(
+            "backends disagree on {:?}: eth2_ssz returned {:?}, ssz_new returned {:?}",
+            data, legacy, new
+        ),
+    }
+}
+
+#[derive(Encode, Decode, SszType, SszSerialize, SszDeserialize, Clone, PartialEq, Debug)]
+#[ssz(crate = "ssz_new")]
+pub struct Fixed {
+    pub a: u16,
+    pub b: bool,
+    pub c: H256,
+    pub d: FixedVector<u8, U4>,
+}
+
+#[derive(Encode, Decode, SszType, SszSerialize, SszDeserialize, Clone, PartialEq, Debug)]
+#[ssz(crate = "ssz_new")]
+pub struct Variable {
+    pub a: u64,
+    pub b: VariableList<u16, U8>,
+    pub c: Fixed,
+    pub d: BitList<U16>,
+}
+
+#[derive(Encode, Decode, SszType, SszSerialize, SszDeserialize, Clone, PartialEq, Debug)]
+#[ssz(crate = "ssz_new")]
+pub struct Nested {
+    pub a: Variable,
+    pub b: VariableList<Variable, U4>,
+    pub c: u32,
+    pub d: Vec<Fixed>,
+}
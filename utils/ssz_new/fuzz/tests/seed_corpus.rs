@@ -0,0 +1,176 @@
+//! Runs the check of every fuzz target over a small seed corpus, so the backends are compared by
+//! `cargo test` as well as by the fuzzer.
+
+use std::fmt::Debug;
+
+use ethereum_types::H256;
+use ssz_new_fuzz::*;
+use ssz_types::{typenum::*, BitList, FixedVector, VariableList};
+
+/// Inputs worth trying for any type: short ones, and offsets pointing inside or past the bytes.
+fn trivial_seeds() -> Vec<Vec<u8>> {
+    vec![
+        vec![],
+        vec![0],
+        vec![1],
+        vec![2],
+        vec![0xff],
+        vec![0; 4],
+        vec![4, 0, 0, 0],
+        vec![8, 0, 0, 0],
+        vec![0xff; 4],
+        vec![252, 255, 255, 255],
+        vec![4, 0, 0, 0, 152, 3],
+        vec![8, 0, 0, 0, 255, 255, 255, 255, 1],
+        vec![0; 32],
+        vec![0xff; 33],
+    ]
+}
+
+/// Checks the trivial seeds, and the encoding of each value along with all its prefixes, a trailing
+/// byte and every single byte changed.
+fn check_seeds<T>(values: Vec<T>)
+where
+    T: ssz::Decode + ssz::Encode + ssz_new::Deserialize + ssz_new::Serialize + PartialEq + Debug,
+{
+    let mut seeds = trivial_seeds();
+    for value in values {
+        let bytes = value.as_ssz_bytes();
+        for len in 0..bytes.len() {
+            seeds.push(bytes[..len].to_vec());
+        }
+        for index in 0..bytes.len() {
+            for change in &[1, 4, 0x80, 0xff] {
+                let mut changed = bytes.clone();
+                changed[index] ^= change;
+                seeds.push(changed);
+            }
+        }
+        let mut longer = bytes.clone();
+        longer.push(0);
+        seeds.push(longer);
+        seeds.push(bytes);
+    }
+
+    for seed in seeds {
+        assert_backends_agree::<T>(&seed);
+    }
+}
+
+fn bitlist<N: Unsigned + Clone>(bits: &[bool]) -> BitList<N> {
+    let mut bitlist = BitList::with_capacity(bits.len()).unwrap();
+    for (index, &bit) in bits.iter().enumerate() {
+        bitlist.set(index, bit).unwrap();
+    }
+    bitlist
+}
+
+fn fixed(a: u16) -> Fixed {
+    Fixed {
+        a,
+        b: a % 2 == 0,
+        c: H256::from_low_u64_be(u64::from(a)),
+        d: FixedVector::from(vec![1, 2, 3, 4]),
+    }
+}
+
+fn variable(a: u64) -> Variable {
+    Variable {
+        a,
+        b: VariableList::from(vec![1, 2, 3]),
+        c: fixed(a as u16),
+        d: bitlist(&[true, false, true]),
+    }
+}
+
+#[test]
+fn bool() {
+    check_seeds(vec![true, false]);
+}
+
+#[test]
+fn uints() {
+    check_seeds(vec![0u8, 1, 255]);
+    check_seeds(vec![0u16, 1, 0x1234]);
+    check_seeds(vec![0u32, 1, 0x1234_5678]);
+    check_seeds(vec![0u64, 1, u64::MAX]);
+}
+
+#[test]
+fn hash256() {
+    check_seeds(vec![H256::zero(), H256::from_low_u64_be(42)]);
+}
+
+#[test]
+fn vec_u16() {
+    check_seeds(vec![vec![], vec![1u16, 2, 3]]);
+}
+
+#[test]
+fn vec_vec_u8() {
+    check_seeds(vec![vec![], vec![vec![1u8, 2], vec![], vec![3]]]);
+}
+
+#[test]
+fn variable_list() {
+    check_seeds::<VariableList<u64, U16>>(vec![
+        VariableList::from(vec![]),
+        VariableList::from(vec![1, 2, 3]),
+    ]);
+}
+
+#[test]
+fn variable_list_of_lists() {
+    check_seeds::<VariableList<VariableList<u8, U8>, U8>>(vec![
+        VariableList::from(vec![]),
+        VariableList::from(vec![
+            VariableList::from(vec![1, 2]),
+            VariableList::from(vec![]),
+            VariableList::from(vec![3]),
+        ]),
+    ]);
+}
+
+#[test]
+fn fixed_vector() {
+    check_seeds::<FixedVector<u16, U8>>(vec![FixedVector::from(vec![1, 2, 3, 4, 5, 6, 7, 8])]);
+}
+
+#[test]
+fn fixed_vector_of_lists() {
+    check_seeds::<FixedVector<Vec<u8>, U4>>(vec![FixedVector::from(vec![
+        vec![1, 2],
+        vec![],
+        vec![3],
+        vec![],
+    ])]);
+}
+
+#[test]
+fn bitlist_u64() {
+    check_seeds::<BitList<U64>>(vec![
+        bitlist(&[]),
+        bitlist(&[true, false, true]),
+        bitlist(&[true; 64]),
+    ]);
+}
+
+#[test]
+fn fixed_container() {
+    check_seeds(vec![fixed(0), fixed(7)]);
+}
+
+#[test]
+fn variable_container() {
+    check_seeds(vec![variable(0), variable(7)]);
+}
+
+#[test]
+fn nested_container() {
+    check_seeds(vec![Nested {
+        a: variable(1),
+        b: VariableList::from(vec![variable(2), variable(3)]),
+        c: 4,
+        d: vec![fixed(5)],
+    }]);
+}
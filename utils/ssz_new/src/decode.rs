@@ -1,6 +1,9 @@
 #![allow(clippy::use_self)]
 
 use crate::*;
+use ethereum_types::{H256, U256};
+use std::convert::TryInto;
+use std::sync::Arc;
 
 macro_rules! deserialize_for_uintn {
     ( $(($type_ident: ty, $size_in_bits: expr)),* ) => { $(
@@ -17,19 +20,18 @@ macro_rules! deserialize_for_uintn {
                     })
                 }
             }
-
-            fn is_variable_size() -> bool {
-                false
-            }
-
-            fn fixed_length() -> usize {
-                $size_in_bits / 8
-            }
         }
     )* };
 }
 
-deserialize_for_uintn!((u8, 8), (u16, 16), (u32, 32), (u64, 64));
+deserialize_for_uintn!(
+    (u8, 8),
+    (u16, 16),
+    (u32, 32),
+    (u64, 64),
+    (u128, 128),
+    (usize, std::mem::size_of::<usize>() * 8)
+);
 
 impl Deserialize for bool {
     fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
@@ -49,48 +51,126 @@ impl Deserialize for bool {
             })
         }
     }
+}
 
-    fn is_variable_size() -> bool {
-        false
+/// The SSZ `Union[None, T]` type.
+impl<T: Deserialize> Deserialize for Option<T> {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        match deserialize_union_selector(bytes)? {
+            (0, []) => Ok(None),
+            (0, _) => Err(Error::InvalidByteLength {
+                got: bytes.len(),
+                required: 1,
+            }),
+            (1, value_bytes) => T::deserialize(value_bytes).map(Some),
+            (selector, _) => Err(Error::InvalidUnionSelector(selector)),
+        }
     }
+}
 
-    fn fixed_length() -> usize {
-        1
+impl<T: Deserialize> Deserialize for Vec<T> {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        deserialize_with_max(bytes, usize::MAX)
     }
 }
 
-impl<T: Deserialize> Deserialize for Vec<T> {
+impl Deserialize for H256 {
     fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
-        let bytes_len = bytes.len();
-
-        if bytes.is_empty() {
-            Ok(vec![])
-        } else if T::is_variable_size() {
-            deserialize_variable_sized_items(bytes)
-        } else if bytes_len % T::fixed_length() == 0 {
-            let mut result = Vec::with_capacity(bytes.len() / T::fixed_length());
-            for chunk in bytes.chunks(T::fixed_length()) {
-                result.push(T::deserialize(chunk)?);
-            }
+        if bytes.len() == Self::fixed_length() {
+            Ok(Self::from_slice(bytes))
+        } else {
+            Err(Error::InvalidByteLength {
+                got: bytes.len(),
+                required: Self::fixed_length(),
+            })
+        }
+    }
+}
 
-            Ok(result)
+impl Deserialize for U256 {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() == Self::fixed_length() {
+            Ok(Self::from_little_endian(bytes))
         } else {
             Err(Error::InvalidByteLength {
-                got: bytes_len,
-                required: bytes.len() / T::fixed_length() + 1,
+                got: bytes.len(),
+                required: Self::fixed_length(),
             })
         }
     }
+}
 
-    fn is_variable_size() -> bool {
-        true
+/// The SSZ `Vector[T, N]` type.
+impl<T: Deserialize, const N: usize> Deserialize for [T; N] {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let items = if T::is_variable_size() {
+            deserialize_with_max(bytes, N)?
+        } else {
+            let item_length = T::fixed_length();
+            if bytes.len() != N * item_length {
+                return Err(Error::InvalidByteLength {
+                    got: bytes.len(),
+                    required: N * item_length,
+                });
+            }
+
+            (0..N)
+                .map(|index| {
+                    T::deserialize(&bytes[index * item_length..][..item_length])
+                        .map_err(|error| error.in_item(index, index * item_length))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let items_count = items.len();
+        items.try_into().map_err(|_| {
+            Error::InvalidBytes(format!(
+                "Cannot parse a vector of {} items from {} items",
+                N, items_count
+            ))
+        })
     }
+}
 
-    fn fixed_length() -> usize {
-        BYTES_PER_LENGTH_OFFSET
+impl<T: Deserialize> Deserialize for Box<T> {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        T::deserialize(bytes).map(Box::new)
+    }
+}
+
+impl<T: Deserialize> Deserialize for Arc<T> {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        T::deserialize(bytes).map(Arc::new)
     }
 }
 
+/// Tuples are deserialized as containers with the elements as fields.
+macro_rules! deserialize_for_tuple {
+    ( $( ( $($type_ident: ident),+ ) ),* ) => { $(
+        impl<$($type_ident: Deserialize),+> Deserialize for ($($type_ident,)+) {
+            fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+                let mut decoder = Decoder::for_bytes(bytes);
+
+                $(
+                    decoder.next_type::<$type_ident>()?;
+                )+
+
+                Ok(($(
+                    decoder.deserialize_next::<$type_ident>()?,
+                )+))
+            }
+        }
+    )* };
+}
+
+deserialize_for_tuple!(
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +322,114 @@ mod tests {
         assert!(bool::deserialize(&[0_u8, 0_u8]).is_err());
     }
 
+    #[test]
+    fn h256() {
+        assert_eq!(H256::deserialize(&[0; 32]).expect("Test"), H256::zero());
+        assert_eq!(
+            H256::deserialize(&[0xab; 32]).expect("Test"),
+            H256::repeat_byte(0xab)
+        );
+        assert!(H256::deserialize(&[0; 31]).is_err());
+        assert!(H256::deserialize(&[0; 33]).is_err());
+    }
+
+    #[test]
+    fn u8_array() {
+        assert_eq!(
+            <[u8; 4]>::deserialize(&[1, 2, 3, 4]).expect("Test"),
+            [1, 2, 3, 4]
+        );
+        assert_eq!(<[u8; 32]>::deserialize(&[7; 32]).expect("Test"), [7; 32]);
+        assert!(<[u8; 4]>::deserialize(&[1, 2, 3]).is_err());
+        assert!(<[u8; 32]>::deserialize(&[0; 4]).is_err());
+    }
+
+    #[test]
+    fn u128() {
+        assert_eq!(u128::deserialize(&[0; 16]).expect("Test"), 0);
+        assert_eq!(
+            u128::deserialize(&[vec![2, 1], vec![0; 14]].concat()).expect("Test"),
+            0x0102
+        );
+        assert!(u128::deserialize(&[0; 8]).is_err());
+    }
+
+    #[test]
+    fn u256() {
+        assert_eq!(U256::deserialize(&[0; 32]).expect("Test"), U256::zero());
+        assert_eq!(
+            U256::deserialize(&[vec![2, 1], vec![0; 30]].concat()).expect("Test"),
+            U256::from(0x0102)
+        );
+        assert!(U256::deserialize(&[0; 31]).is_err());
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!(
+            <[u16; 2]>::deserialize(&[1, 0, 2, 0]).expect("Test"),
+            [1, 2]
+        );
+        assert_eq!(
+            <[Vec<u8>; 2]>::deserialize(&[8, 0, 0, 0, 10, 0, 0, 0, 1, 2, 3]).expect("Test"),
+            [vec![1, 2], vec![3]]
+        );
+
+        // wrong number of items
+        assert!(<[u16; 2]>::deserialize(&[1, 0]).is_err());
+        assert!(<[Vec<u8>; 3]>::deserialize(&[8, 0, 0, 0, 10, 0, 0, 0, 1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn smart_pointers() {
+        assert_eq!(<Box<u16>>::deserialize(&[5, 0]).expect("Test"), Box::new(5));
+        assert_eq!(
+            <Arc<Vec<u8>>>::deserialize(&[1, 2]).expect("Test"),
+            Arc::new(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        assert_eq!(<(u8, u16)>::deserialize(&[1, 2, 0]).expect("Test"), (1, 2));
+        assert_eq!(
+            <(u8, Vec<u8>, bool)>::deserialize(&[1, 6, 0, 0, 0, 1, 2, 3]).expect("Test"),
+            (1, vec![2, 3], true)
+        );
+
+        // trailing bytes after the fixed part
+        assert!(<(u8, u16)>::deserialize(&[1, 2, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(<Option<u16>>::deserialize(&[0]).expect("Test"), None);
+        assert_eq!(
+            <Option<u16>>::deserialize(&[1, 2, 1]).expect("Test"),
+            Some(0x0102)
+        );
+        assert_eq!(
+            <Option<Vec<u8>>>::deserialize(&[1, 1, 2]).expect("Test"),
+            Some(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn option_error() {
+        // missing selector
+        assert!(<Option<u16>>::deserialize(&[]).is_err());
+
+        // bytes after `None`
+        assert!(<Option<u16>>::deserialize(&[0, 1]).is_err());
+
+        // selector out of range
+        assert!(<Option<u16>>::deserialize(&[2, 2, 1]).is_err());
+        assert!(<Option<u16>>::deserialize(&[128]).is_err());
+
+        // wrong bytes to deserialize value
+        assert!(<Option<u16>>::deserialize(&[1, 2]).is_err());
+    }
+
     #[test]
     fn vector_fixed() {
         assert_eq!(<Vec<u8>>::deserialize(&[]).expect("Test"), vec![]);
@@ -1,102 +1,163 @@
 #![allow(clippy::use_self)] // there is probably a bug with generic vectors
 
-use crate::utils::serialize_offset;
 use crate::*;
+use ethereum_types::{H256, U256};
+use std::sync::Arc;
 
 macro_rules! serialize_for_uintn {
     ( $($type_ident: ty),* ) => { $(
         impl Serialize for $type_ident {
-            fn serialize(&self) -> Result<Vec<u8>, Error> {
-                Ok(self.to_le_bytes().to_vec())
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+                buffer.extend_from_slice(&self.to_le_bytes());
+                Ok(())
             }
 
-            fn is_variable_size() -> bool {
-                false
+            fn serialized_len(&self) -> usize {
+                std::mem::size_of::<$type_ident>()
             }
         }
     )* };
 }
 
-serialize_for_uintn!(u8, u16, u32, u64);
+serialize_for_uintn!(u8, u16, u32, u64, u128, usize);
 
 impl Serialize for bool {
-    fn serialize(&self) -> Result<Vec<u8>, Error> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
         let byte = if *self { 0b0000_0001 } else { 0b0000_0000 };
 
-        Ok(vec![byte])
+        buffer.push(byte);
+        Ok(())
     }
 
-    fn is_variable_size() -> bool {
-        false
+    fn serialized_len(&self) -> usize {
+        1
     }
 }
 
-impl<T: Serialize> Serialize for Vec<T> {
-    fn serialize(&self) -> Result<Vec<u8>, Error> {
-        let mut fixed_parts = Vec::with_capacity(self.len());
-        for element in self {
-            fixed_parts.push(if T::is_variable_size() {
-                None
-            } else {
-                Some(element.serialize()?)
-            });
+/// The SSZ `Union[None, T]` type.
+impl<T: Serialize> Serialize for Option<T> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        match self {
+            None => serialize_union_selector_into(0, buffer),
+            Some(value) => {
+                serialize_union_selector_into(1, buffer)?;
+                value.serialize_into(buffer)
+            }
         }
+    }
 
-        let mut variable_parts = Vec::with_capacity(self.len());
-        for element in self {
-            variable_parts.push(if T::is_variable_size() {
-                element.serialize()?
-            } else {
-                vec![]
-            });
-        }
+    fn serialized_len(&self) -> usize {
+        1 + self.as_ref().map_or(0, Serialize::serialized_len)
+    }
+}
 
-        let fixed_length: usize = fixed_parts
-            .iter()
-            .map(|part| match part {
-                Some(bytes) => bytes.len(),
-                None => BYTES_PER_LENGTH_OFFSET,
-            })
-            .sum();
-
-        let variable_lengths: Vec<usize> = variable_parts.iter().map(std::vec::Vec::len).collect();
-
-        let mut variable_offsets = Vec::with_capacity(self.len());
-        for i in 0..self.len() {
-            let variable_length_sum: usize = variable_lengths[..i].iter().sum();
-            let offset = fixed_length + variable_length_sum;
-            variable_offsets.push(serialize_offset(offset)?);
-        }
+impl<T: Serialize> Serialize for Vec<T> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        serialize_items_into(self, buffer)
+    }
 
-        let fixed_parts: Vec<&Vec<u8>> = fixed_parts
-            .iter()
-            .enumerate()
-            .map(|(i, part)| match part {
-                Some(bytes) => bytes,
-                None => &variable_offsets[i],
-            })
-            .collect();
-
-        let variable_lengths_sum: usize = variable_lengths.iter().sum();
-        let total_bytes = fixed_length + variable_lengths_sum;
-        let mut result = Vec::with_capacity(total_bytes);
-
-        for part in fixed_parts {
-            result.extend(part);
-        }
+    fn serialized_len(&self) -> usize {
+        serialized_items_len(self)
+    }
+}
 
-        for part in variable_parts {
-            result.extend(part);
-        }
+impl Serialize for H256 {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        buffer.extend_from_slice(self.as_bytes());
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        32
+    }
+}
+
+impl Serialize for U256 {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        let mut bytes = [0; 32];
+        self.to_little_endian(&mut bytes);
+        buffer.extend_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        32
+    }
+}
 
-        Ok(result)
+/// The SSZ `Vector[T, N]` type.
+impl<T: Serialize, const N: usize> Serialize for [T; N] {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        serialize_items_into(self, buffer)
     }
 
-    fn is_variable_size() -> bool {
-        true
+    fn serialized_len(&self) -> usize {
+        serialized_items_len(self)
     }
 }
 
+impl<T: Serialize> Serialize for Box<T> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        T::serialize_into(self, buffer)
+    }
+
+    fn serialized_len(&self) -> usize {
+        T::serialized_len(self)
+    }
+}
+
+impl<T: Serialize> Serialize for Arc<T> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        T::serialize_into(self, buffer)
+    }
+
+    fn serialized_len(&self) -> usize {
+        T::serialized_len(self)
+    }
+}
+
+/// Tuples are serialized as containers with the elements as fields.
+macro_rules! serialize_for_tuple {
+    ( $( ( $($index: tt: $type_ident: ident),+ ) ),* ) => { $(
+        impl<$($type_ident: Serialize),+> Serialize for ($($type_ident,)+) {
+            #[allow(unused_assignments)]
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+                let mut variable_offset = 0 $( + $type_ident::fixed_length() )+;
+
+                $(
+                    if $type_ident::is_variable_size() {
+                        serialize_offset_into(variable_offset, buffer)?;
+                        variable_offset += self.$index.serialized_len();
+                    } else {
+                        self.$index.serialize_into(buffer)?;
+                    }
+                )+
+
+                $(
+                    if $type_ident::is_variable_size() {
+                        self.$index.serialize_into(buffer)?;
+                    }
+                )+
+
+                Ok(())
+            }
+
+            fn serialized_len(&self) -> usize {
+                0 $( + $type_ident::fixed_length() )+
+                    $( + if $type_ident::is_variable_size() { self.$index.serialized_len() } else { 0 } )+
+            }
+        }
+    )* };
+}
+
+serialize_for_tuple!(
+    (0: A, 1: B),
+    (0: A, 1: B, 2: C),
+    (0: A, 1: B, 2: C, 3: D),
+    (0: A, 1: B, 2: C, 3: D, 4: E),
+    (0: A, 1: B, 2: C, 3: D, 4: E, 5: F)
+);
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -241,6 +302,78 @@ mod test {
         assert_eq!(false.serialize().expect("Test"), vec![0b0000_0000]);
     }
 
+    #[test]
+    fn h256() {
+        assert_eq!(H256::zero().serialize().expect("Test"), vec![0; 32]);
+        assert_eq!(
+            H256::repeat_byte(0xab).serialize().expect("Test"),
+            vec![0xab; 32]
+        );
+    }
+
+    #[test]
+    fn u8_array() {
+        assert_eq!([1_u8, 2, 3, 4].serialize().expect("Test"), vec![1, 2, 3, 4]);
+        assert_eq!([7_u8; 32].serialize().expect("Test"), vec![7; 32]);
+    }
+
+    #[test]
+    fn u128() {
+        assert_eq!(0_u128.serialize().expect("Test"), vec![0; 16]);
+        assert_eq!(
+            0x0102_u128.serialize().expect("Test"),
+            [vec![2, 1], vec![0; 14]].concat()
+        );
+    }
+
+    #[test]
+    fn u256() {
+        assert_eq!(U256::zero().serialize().expect("Test"), vec![0; 32]);
+        assert_eq!(
+            U256::from(0x0102).serialize().expect("Test"),
+            [vec![2, 1], vec![0; 30]].concat()
+        );
+        assert_eq!(U256::max_value().serialize().expect("Test"), vec![0xff; 32]);
+    }
+
+    #[test]
+    fn array() {
+        assert_eq!([1_u16, 2].serialize().expect("Test"), vec![1, 0, 2, 0]);
+        assert_eq!(
+            [vec![1_u8, 2], vec![3]].serialize().expect("Test"),
+            vec![8, 0, 0, 0, 10, 0, 0, 0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn smart_pointers() {
+        assert_eq!(Box::new(5_u16).serialize().expect("Test"), vec![5, 0]);
+        assert_eq!(
+            Arc::new(vec![1_u8, 2]).serialize().expect("Test"),
+            vec![1, 2]
+        );
+    }
+
+    #[test]
+    fn tuple() {
+        assert_eq!((1_u8, 2_u16).serialize().expect("Test"), vec![1, 2, 0]);
+        assert_eq!(
+            (1_u8, vec![2_u8, 3], true).serialize().expect("Test"),
+            vec![1, 6, 0, 0, 0, 1, 2, 3]
+        );
+        assert_eq!((1_u8, vec![2_u8, 3], true).serialized_len(), 8);
+    }
+
+    #[test]
+    fn option() {
+        assert_eq!(None::<u16>.serialize().expect("Test"), vec![0]);
+        assert_eq!(Some(0x0102_u16).serialize().expect("Test"), vec![1, 2, 1]);
+        assert_eq!(
+            Some(vec![1_u8, 2]).serialize().expect("Test"),
+            vec![1, 1, 2]
+        );
+    }
+
     #[test]
     fn vector_fixed() {
         let vec: Vec<u8> = vec![];
@@ -303,4 +436,32 @@ mod test {
             vec![8, 0, 0, 0, 11, 0, 0, 0, 1, 2, 3, 4, 5, 6]
         );
     }
+
+    #[test]
+    fn serialize_into_appends() {
+        let mut buffer = vec![9];
+        vec![vec![1_u8, 2, 3], vec![4, 5, 6]]
+            .serialize_into(&mut buffer)
+            .expect("Test");
+        Some(7_u16).serialize_into(&mut buffer).expect("Test");
+
+        assert_eq!(
+            buffer,
+            vec![9, 8, 0, 0, 0, 11, 0, 0, 0, 1, 2, 3, 4, 5, 6, 1, 7, 0]
+        );
+    }
+
+    #[test]
+    fn serialized_len() {
+        assert_eq!(5_u64.serialized_len(), 8);
+        assert_eq!(true.serialized_len(), 1);
+        assert_eq!(H256::zero().serialized_len(), 32);
+        assert_eq!(None::<u16>.serialized_len(), 1);
+        assert_eq!(Some(vec![1_u8, 2]).serialized_len(), 3);
+        assert_eq!(Vec::<u64>::new().serialized_len(), 0);
+        assert_eq!(vec![1_u64, 2, 3].serialized_len(), 24);
+
+        let vec: Vec<Vec<u8>> = vec![vec![1, 2, 3], vec![], vec![4]];
+        assert_eq!(vec.serialized_len(), vec.serialize().expect("Test").len());
+    }
 }
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum Error {
+    TooBigOffset(usize),
+    InvalidByteLength {
+        required: usize,
+        got: usize,
+    },
+    BitsOverflow {
+        bits_count: usize,
+        max_bits: usize,
+    },
+    NoOffsetsLeft,
+    InvalidBytes(String),
+    TooMuchElements {
+        got: usize,
+        max: usize,
+    },
+    InvalidUnionSelector(u8),
+    /// The first offset points before the end of the fixed part.
+    OffsetIntoFixedPortion(usize),
+    /// The first offset points past the end of the fixed part.
+    OffsetSkipsFixedPortion(usize),
+    /// An offset is smaller than the one before it.
+    OffsetOutOfOrder(usize),
+    /// An offset points past the end of the bytes.
+    OffsetOutOfBounds(usize),
+    /// The fixed part of a list of variable size elements is not a whole number of offsets.
+    InvalidListFixedBytesLength(usize),
+    /// Reading the bytes to decode failed.
+    Io(io::Error),
+    /// Decoding a value nested in containers, lists or vectors failed with `error`.
+    ///
+    /// `container` is the type of the outermost container if it was derived, and `offset` is
+    /// where the bytes of the value start in the bytes being decoded.
+    Nested {
+        container: Option<&'static str>,
+        path: Vec<PathSegment>,
+        offset: usize,
+        error: Box<Error>,
+    },
+}
+
+/// A step on the way to a nested value: a field of a container or an item of a list or vector.
+#[derive(Clone, PartialEq, Debug)]
+pub enum PathSegment {
+    Field(&'static str),
+    Index(usize),
+}
+
+impl Error {
+    /// Marks `self` as coming from the field `name` of `container`, whose bytes start `offset`
+    /// bytes into the container.
+    pub fn in_field(self, container: &'static str, name: &'static str, offset: usize) -> Self {
+        self.nest(Some(container), PathSegment::Field(name), offset)
+    }
+
+    /// Marks `self` as coming from the item at `index`, whose bytes start `offset` bytes into
+    /// the list or vector.
+    pub fn in_item(self, index: usize, offset: usize) -> Self {
+        self.nest(None, PathSegment::Index(index), offset)
+    }
+
+    /// Returns the error that caused `self`, without the path leading to it.
+    pub fn innermost(&self) -> &Self {
+        match self {
+            Error::Nested { error, .. } => error,
+            error => error,
+        }
+    }
+
+    fn nest(self, container: Option<&'static str>, segment: PathSegment, offset: usize) -> Self {
+        match self {
+            Error::Nested {
+                mut path,
+                offset: nested_offset,
+                error,
+                ..
+            } => {
+                path.insert(0, segment);
+                Error::Nested {
+                    container,
+                    path,
+                    offset: offset + nested_offset,
+                    error,
+                }
+            }
+            error => Error::Nested {
+                container,
+                path: vec![segment],
+                offset,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::TooBigOffset(offset) => write!(f, "offset {} does not fit in 4 bytes", offset),
+            Error::InvalidByteLength { required, got } => {
+                write!(f, "expected {} bytes, got {}", required, got)
+            }
+            Error::BitsOverflow {
+                bits_count,
+                max_bits,
+            } => write!(f, "{} bits do not fit in {}", bits_count, max_bits),
+            Error::NoOffsetsLeft => write!(f, "no offsets left"),
+            Error::InvalidBytes(message) => write!(f, "invalid bytes: {}", message),
+            Error::TooMuchElements { got, max } => {
+                write!(f, "expected at most {} elements, got {}", max, got)
+            }
+            Error::InvalidUnionSelector(selector) => {
+                write!(f, "invalid union selector {}", selector)
+            }
+            Error::OffsetIntoFixedPortion(offset) => {
+                write!(f, "offset {} points into the fixed part", offset)
+            }
+            Error::OffsetSkipsFixedPortion(offset) => {
+                write!(f, "offset {} skips bytes after the fixed part", offset)
+            }
+            Error::OffsetOutOfOrder(offset) => {
+                write!(f, "offset {} is smaller than the previous one", offset)
+            }
+            Error::OffsetOutOfBounds(offset) => {
+                write!(f, "offset {} points past the end of the bytes", offset)
+            }
+            Error::InvalidListFixedBytesLength(length) => write!(
+                f,
+                "{} bytes of offsets are not a whole number of offsets",
+                length
+            ),
+            Error::Io(error) => write!(f, "I/O error: {}", error),
+            Error::Nested {
+                container,
+                path,
+                offset,
+                error,
+            } => {
+                let mut path = path.iter().map(ToString::to_string).collect::<String>();
+                match container {
+                    Some(container) => path.insert_str(0, container),
+                    // A path starting with a field reads better without the leading dot.
+                    None if path.starts_with('.') => {
+                        path.remove(0);
+                    }
+                    None => {}
+                }
+                write!(f, "{} (at byte {}): {}", path, offset, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_path() {
+        let error = Error::InvalidBytes("bad bits".to_string())
+            .in_field("Attestation", "aggregation_bits", 4)
+            .in_item(3, 100)
+            .in_field("BeaconBlockBody", "attestations", 200)
+            .in_field("BeaconBlock", "body", 76);
+
+        match &error {
+            Error::Nested {
+                container,
+                path,
+                offset,
+                ..
+            } => {
+                assert_eq!(*container, Some("BeaconBlock"));
+                assert_eq!(
+                    *path,
+                    vec![
+                        PathSegment::Field("body"),
+                        PathSegment::Field("attestations"),
+                        PathSegment::Index(3),
+                        PathSegment::Field("aggregation_bits"),
+                    ]
+                );
+                assert_eq!(*offset, 380);
+            }
+            error => panic!("unexpected error: {:?}", error),
+        }
+        assert!(matches!(error.innermost(), Error::InvalidBytes(_)));
+        assert_eq!(
+            error.to_string(),
+            "BeaconBlock.body.attestations[3].aggregation_bits (at byte 380): invalid bytes: bad bits"
+        );
+    }
+
+    #[test]
+    fn path_without_container() {
+        let error = Error::NoOffsetsLeft.in_item(2, 8);
+        assert_eq!(error.to_string(), "[2] (at byte 8): no offsets left");
+
+        let error = Error::NoOffsetsLeft.in_field("Fixed", "a", 0).in_item(1, 3);
+        assert_eq!(error.to_string(), "[1].a (at byte 3): no offsets left");
+    }
+}
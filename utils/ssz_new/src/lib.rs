@@ -1,32 +1,49 @@
 mod decode;
 mod encode;
+mod error;
+mod ssz_type;
+mod stream;
 mod utils;
-
-pub use ssz_derive::{SszDeserialize, SszSerialize};
-pub use utils::{deserialize_offset, deserialize_variable_sized_items, serialize_offset, Decoder};
+mod view;
+
+pub use error::{Error, PathSegment};
+pub use ssz_derive::{SszDeserialize, SszSerialize, SszType, SszView};
+pub use stream::ItemsReader;
+pub use utils::{
+    deserialize_offset, deserialize_union_selector, deserialize_variable_sized_items,
+    deserialize_with_max, serialize_items_into, serialize_offset, serialize_offset_into,
+    serialize_union_selector, serialize_union_selector_into, serialized_items_len, Decoder,
+};
+pub use view::{ContainerView, Sequence, View};
 
 pub const BYTES_PER_LENGTH_OFFSET: usize = 4;
+pub const MAX_UNION_SELECTOR: u8 = 127;
 
-pub trait Serialize {
-    fn serialize(&self) -> Result<Vec<u8>, Error>;
-
+/// Describes the layout of a type's serialization, shared by `Serialize` and `Deserialize`.
+pub trait SszType {
+    /// Returns `true` if the length of the serialization depends on the value.
     fn is_variable_size() -> bool;
+
+    /// Returns the number of bytes the type takes in the fixed part of an enclosing container:
+    /// the length of its serialization if it is fixed size, and `BYTES_PER_LENGTH_OFFSET`
+    /// otherwise.
+    fn fixed_length() -> usize;
 }
 
-pub trait Deserialize: Sized {
-    fn deserialize(bytes: &[u8]) -> Result<Self, Error>;
+pub trait Serialize: SszType {
+    fn serialize(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(self.serialized_len());
+        self.serialize_into(&mut bytes)?;
+        Ok(bytes)
+    }
 
-    fn is_variable_size() -> bool;
+    /// Appends the serialization of `self` to `buffer`.
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error>;
 
-    fn fixed_length() -> usize;
+    /// Returns the number of bytes `serialize_into` appends.
+    fn serialized_len(&self) -> usize;
 }
 
-#[derive(Debug)]
-pub enum Error {
-    TooBigOffset(usize),
-    InvalidByteLength { required: usize, got: usize },
-    BitsOverflow { bits_count: usize, max_bits: usize },
-    NoOffsetsLeft,
-    InvalidBytes(String),
-    TooMuchElements { got: usize, max: usize },
+pub trait Deserialize: SszType + Sized {
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error>;
 }
@@ -0,0 +1,141 @@
+use crate::*;
+use ethereum_types::{H256, U256};
+use std::sync::Arc;
+
+macro_rules! ssz_type_for_fixed_size {
+    ( $(($type_ident: ty, $len: expr)),* ) => { $(
+        impl SszType for $type_ident {
+            fn is_variable_size() -> bool {
+                false
+            }
+
+            fn fixed_length() -> usize {
+                $len
+            }
+        }
+    )* };
+}
+
+ssz_type_for_fixed_size!(
+    (u8, 1),
+    (u16, 2),
+    (u32, 4),
+    (u64, 8),
+    (u128, 16),
+    (usize, std::mem::size_of::<usize>()),
+    (bool, 1),
+    (H256, 32),
+    (U256, 32)
+);
+
+impl<T: SszType, const N: usize> SszType for [T; N] {
+    fn is_variable_size() -> bool {
+        T::is_variable_size()
+    }
+
+    fn fixed_length() -> usize {
+        if T::is_variable_size() {
+            BYTES_PER_LENGTH_OFFSET
+        } else {
+            N * T::fixed_length()
+        }
+    }
+}
+
+impl<T: SszType> SszType for Box<T> {
+    fn is_variable_size() -> bool {
+        T::is_variable_size()
+    }
+
+    fn fixed_length() -> usize {
+        T::fixed_length()
+    }
+}
+
+impl<T: SszType> SszType for Arc<T> {
+    fn is_variable_size() -> bool {
+        T::is_variable_size()
+    }
+
+    fn fixed_length() -> usize {
+        T::fixed_length()
+    }
+}
+
+macro_rules! ssz_type_for_tuple {
+    ( $( ( $($type_ident: ident),+ ) ),* ) => { $(
+        impl<$($type_ident: SszType),+> SszType for ($($type_ident,)+) {
+            fn is_variable_size() -> bool {
+                $( $type_ident::is_variable_size() )||+
+            }
+
+            fn fixed_length() -> usize {
+                if Self::is_variable_size() {
+                    BYTES_PER_LENGTH_OFFSET
+                } else {
+                    0 $( + $type_ident::fixed_length() )+
+                }
+            }
+        }
+    )* };
+}
+
+ssz_type_for_tuple!(
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
+/// The SSZ `Union[None, T]` type.
+impl<T> SszType for Option<T> {
+    fn is_variable_size() -> bool {
+        true
+    }
+
+    fn fixed_length() -> usize {
+        BYTES_PER_LENGTH_OFFSET
+    }
+}
+
+impl<T> SszType for Vec<T> {
+    fn is_variable_size() -> bool {
+        true
+    }
+
+    fn fixed_length() -> usize {
+        BYTES_PER_LENGTH_OFFSET
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_size() {
+        assert!(!u16::is_variable_size());
+        assert_eq!(u16::fixed_length(), 2);
+        assert_eq!(bool::fixed_length(), 1);
+        assert_eq!(H256::fixed_length(), 32);
+        assert_eq!(<[u8; 4]>::fixed_length(), 4);
+        assert_eq!(<[u16; 3]>::fixed_length(), 6);
+        assert_eq!(<Box<u64>>::fixed_length(), 8);
+        assert!(!<(u8, [u8; 4])>::is_variable_size());
+        assert_eq!(<(u8, [u8; 4])>::fixed_length(), 5);
+    }
+
+    #[test]
+    fn variable_size() {
+        assert!(<Vec<u8>>::is_variable_size());
+        assert_eq!(<Vec<u8>>::fixed_length(), BYTES_PER_LENGTH_OFFSET);
+        assert!(<Option<u64>>::is_variable_size());
+        assert_eq!(<Option<u64>>::fixed_length(), BYTES_PER_LENGTH_OFFSET);
+        assert!(<[Vec<u8>; 2]>::is_variable_size());
+        assert_eq!(<[Vec<u8>; 2]>::fixed_length(), BYTES_PER_LENGTH_OFFSET);
+        assert!(<Arc<Vec<u8>>>::is_variable_size());
+        assert!(<(u8, Vec<u8>)>::is_variable_size());
+        assert_eq!(<(u8, Vec<u8>)>::fixed_length(), BYTES_PER_LENGTH_OFFSET);
+    }
+}
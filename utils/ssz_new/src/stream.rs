@@ -0,0 +1,289 @@
+use crate::*;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// Decodes the items of an SSZ list or vector `S` one at a time from a `Read` source, so the
+/// whole encoding never has to be held in memory.
+///
+/// The reader must hold exactly the encoding of the list. Fixed size items are read one after
+/// another until the end of the reader. For variable size items the offsets are read up front and
+/// checked the same way `deserialize_variable_sized_items` checks them, and then each item is
+/// read and decoded on its own.
+///
+/// The iterator stops after the first error.
+pub struct ItemsReader<R, S> {
+    reader: R,
+    /// The offsets of the variable size items, read on the first call to `next`.
+    offsets: Option<Vec<usize>>,
+    /// The number of bytes read so far.
+    position: usize,
+    index: usize,
+    done: bool,
+    _phantom: PhantomData<fn() -> S>,
+}
+
+impl<R: Read, S: Sequence> ItemsReader<R, S> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offsets: None,
+            position: 0,
+            index: 0,
+            done: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the reader, positioned after the last item read.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_fixed_size_item(&mut self) -> Result<Option<S::Item>, Error> {
+        let item_length = S::Item::fixed_length();
+        let mut bytes = vec![0; item_length];
+        let read = read_up_to(&mut self.reader, &mut bytes)?;
+
+        if read == 0 {
+            return Ok(None);
+        }
+        if read < item_length {
+            return Err(Error::InvalidBytes(format!(
+                "{} bytes do not split into items of {} bytes",
+                self.position + read,
+                item_length
+            )));
+        }
+        self.check_max_len(self.index + 1)?;
+
+        let start = self.position;
+        self.position += item_length;
+        S::Item::deserialize(&bytes)
+            .map(Some)
+            .map_err(|error| error.in_item(self.index, start))
+    }
+
+    fn next_variable_size_item(&mut self) -> Result<Option<S::Item>, Error> {
+        if self.offsets.is_none() {
+            self.offsets = Some(self.read_offsets()?);
+        }
+        let offsets = self.offsets.as_deref().unwrap_or_default();
+
+        let start = match offsets.get(self.index) {
+            Some(&start) => start,
+            None => return Ok(None),
+        };
+        let bytes = match offsets.get(self.index + 1) {
+            Some(&end) => {
+                // The offsets are untrusted, so the buffer only grows as bytes arrive.
+                let mut bytes = vec![];
+                (&mut self.reader)
+                    .take((end - start) as u64)
+                    .read_to_end(&mut bytes)
+                    .map_err(Error::Io)?;
+                if bytes.len() < end - start {
+                    return Err(Error::OffsetOutOfBounds(end));
+                }
+                bytes
+            }
+            None => {
+                let mut bytes = vec![];
+                self.reader.read_to_end(&mut bytes).map_err(Error::Io)?;
+                bytes
+            }
+        };
+
+        self.position += bytes.len();
+        S::Item::deserialize(&bytes)
+            .map(Some)
+            .map_err(|error| error.in_item(self.index, start))
+    }
+
+    /// Reads the offsets at the start of a list of variable size items.
+    fn read_offsets(&mut self) -> Result<Vec<usize>, Error> {
+        let first_offset = match self.read_offset()? {
+            Some(offset) => offset,
+            None => return Ok(vec![]),
+        };
+        if first_offset < BYTES_PER_LENGTH_OFFSET {
+            return Err(Error::OffsetIntoFixedPortion(first_offset));
+        }
+        if !first_offset.is_multiple_of(BYTES_PER_LENGTH_OFFSET) {
+            return Err(Error::InvalidListFixedBytesLength(first_offset));
+        }
+
+        let items_count = first_offset / BYTES_PER_LENGTH_OFFSET;
+        self.check_max_len(items_count)?;
+
+        // The count comes from the bytes, so the offsets are not preallocated.
+        let mut offsets = vec![first_offset];
+        while offsets.len() < items_count {
+            let offset = match self.read_offset()? {
+                Some(offset) => offset,
+                None => {
+                    return Err(Error::InvalidByteLength {
+                        got: self.position,
+                        required: first_offset,
+                    })
+                }
+            };
+            if offset < offsets[offsets.len() - 1] {
+                return Err(Error::OffsetOutOfOrder(offset));
+            }
+            offsets.push(offset);
+        }
+        Ok(offsets)
+    }
+
+    /// Reads the next offset, or returns `None` if the reader is at its end.
+    fn read_offset(&mut self) -> Result<Option<usize>, Error> {
+        let mut bytes = [0; BYTES_PER_LENGTH_OFFSET];
+        match read_up_to(&mut self.reader, &mut bytes)? {
+            0 => Ok(None),
+            BYTES_PER_LENGTH_OFFSET => {
+                self.position += BYTES_PER_LENGTH_OFFSET;
+                deserialize_offset(&bytes).map(Some)
+            }
+            read => Err(Error::InvalidByteLength {
+                got: self.position + read,
+                required: self.position + BYTES_PER_LENGTH_OFFSET,
+            }),
+        }
+    }
+
+    fn check_max_len(&self, items_count: usize) -> Result<(), Error> {
+        match S::max_len() {
+            Some(max) if items_count > max => Err(Error::TooMuchElements {
+                got: items_count,
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<R: Read, S: Sequence> Iterator for ItemsReader<R, S> {
+    type Item = Result<S::Item, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = if S::Item::is_variable_size() {
+            self.next_variable_size_item()
+        } else {
+            self.next_fixed_size_item()
+        };
+
+        match item {
+            Ok(Some(item)) => {
+                self.index += 1;
+                Some(Ok(item))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(error) => {
+                self.done = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Fills `buffer` from `reader`, returning fewer bytes than its length only at the end of the
+/// reader.
+fn read_up_to(reader: &mut impl Read, buffer: &mut [u8]) -> Result<usize, Error> {
+    let mut read = 0;
+    while read < buffer.len() {
+        match reader.read(&mut buffer[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(Error::Io(error)),
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all<S: Sequence>(bytes: &[u8]) -> Result<Vec<S::Item>, Error> {
+        ItemsReader::<_, S>::new(bytes).collect()
+    }
+
+    #[test]
+    fn fixed_size_items() {
+        let bytes = vec![1u16, 2, 3].serialize().expect("Test");
+        assert_eq!(read_all::<Vec<u16>>(&bytes).expect("Test"), vec![1, 2, 3]);
+        assert!(read_all::<Vec<u16>>(&[]).expect("Test").is_empty());
+
+        match read_all::<Vec<u16>>(&[1, 0, 2]) {
+            Err(Error::InvalidBytes(_)) => {}
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn variable_size_items() {
+        let items = vec![vec![1u8, 2], vec![], vec![3]];
+        let bytes = items.serialize().expect("Test");
+        assert_eq!(read_all::<Vec<Vec<u8>>>(&bytes).expect("Test"), items);
+        assert!(read_all::<Vec<Vec<u8>>>(&[]).expect("Test").is_empty());
+    }
+
+    #[test]
+    fn matches_deserialize() {
+        let items = vec![vec![1u16], vec![2, 3], vec![4, 5, 6]];
+        let bytes = items.serialize().expect("Test");
+        assert_eq!(
+            read_all::<Vec<Vec<u16>>>(&bytes).expect("Test"),
+            <Vec<Vec<u16>>>::deserialize(&bytes).expect("Test")
+        );
+    }
+
+    #[test]
+    fn invalid_offsets() {
+        assert!(matches!(
+            read_all::<Vec<Vec<u8>>>(&[0, 0, 0, 0]),
+            Err(Error::OffsetIntoFixedPortion(0))
+        ));
+        assert!(matches!(
+            read_all::<Vec<Vec<u8>>>(&[5, 0, 0, 0, 0, 1]),
+            Err(Error::InvalidListFixedBytesLength(5))
+        ));
+        assert!(matches!(
+            read_all::<Vec<Vec<u8>>>(&[8, 0, 0, 0, 6, 0, 0, 0, 1]),
+            Err(Error::OffsetOutOfOrder(6))
+        ));
+        assert!(matches!(
+            read_all::<Vec<Vec<u8>>>(&[8, 0, 0, 0, 10, 0, 0, 0, 1]),
+            Err(Error::OffsetOutOfBounds(10))
+        ));
+        assert!(matches!(
+            read_all::<Vec<Vec<u8>>>(&[8, 0, 0, 0, 255, 255, 255, 255, 1]),
+            Err(Error::OffsetOutOfBounds(0xffff_ffff))
+        ));
+    }
+
+    #[test]
+    fn item_error_path() {
+        let bytes = [8, 0, 0, 0, 10, 0, 0, 0, 1, 0, 2];
+        let mut reader = ItemsReader::<_, Vec<Vec<bool>>>::new(&bytes[..]);
+        assert_eq!(
+            reader.next().expect("Test").expect("Test"),
+            vec![true, false]
+        );
+
+        let error = reader.next().expect("Test").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[1][0] (at byte 10): invalid bytes: Cannot deserialize bool from 2"
+        );
+        assert!(reader.next().is_none());
+    }
+}
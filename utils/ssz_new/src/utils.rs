@@ -3,8 +3,15 @@ use crate::*;
 const MAX_POSSIBLE_OFFSET_VALUE: usize = usize::max_value() >> (BYTES_PER_LENGTH_OFFSET * 8);
 
 pub fn serialize_offset(offset: usize) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(BYTES_PER_LENGTH_OFFSET);
+    serialize_offset_into(offset, &mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn serialize_offset_into(offset: usize, buffer: &mut Vec<u8>) -> Result<(), Error> {
     if offset < MAX_POSSIBLE_OFFSET_VALUE {
-        Ok(offset.to_le_bytes()[..BYTES_PER_LENGTH_OFFSET].to_vec())
+        buffer.extend_from_slice(&offset.to_le_bytes()[..BYTES_PER_LENGTH_OFFSET]);
+        Ok(())
     } else {
         Err(Error::TooBigOffset(offset))
     }
@@ -23,17 +30,120 @@ pub fn deserialize_offset(bytes: &[u8]) -> Result<usize, Error> {
     }
 }
 
-pub fn deserialize_variable_sized_items<T: Deserialize>(bytes: &[u8]) -> Result<Vec<T>, Error> {
-    let first_offset_bytes = bytes.get(0..BYTES_PER_LENGTH_OFFSET);
-    let first_offset = match first_offset_bytes {
-        Some(bytes) => deserialize_offset(bytes),
+pub fn serialize_union_selector(selector: u8) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::with_capacity(1);
+    serialize_union_selector_into(selector, &mut bytes)?;
+    Ok(bytes)
+}
+
+pub fn serialize_union_selector_into(selector: u8, buffer: &mut Vec<u8>) -> Result<(), Error> {
+    if selector <= MAX_UNION_SELECTOR {
+        buffer.push(selector);
+        Ok(())
+    } else {
+        Err(Error::InvalidUnionSelector(selector))
+    }
+}
+
+/// Splits the bytes of a union into its selector and the bytes of the selected value.
+pub fn deserialize_union_selector(bytes: &[u8]) -> Result<(u8, &[u8]), Error> {
+    match bytes.split_first() {
+        Some((&selector, value_bytes)) if selector <= MAX_UNION_SELECTOR => {
+            Ok((selector, value_bytes))
+        }
+        Some((&selector, _)) => Err(Error::InvalidUnionSelector(selector)),
+        None => Err(Error::InvalidByteLength {
+            required: 1,
+            got: 0,
+        }),
+    }
+}
+
+/// Appends the elements of an SSZ vector or list to `buffer`: the elements themselves if they are
+/// fixed size, otherwise their offsets followed by the elements.
+pub fn serialize_items_into<T: Serialize>(items: &[T], buffer: &mut Vec<u8>) -> Result<(), Error> {
+    if T::is_variable_size() {
+        let mut offset = items.len() * BYTES_PER_LENGTH_OFFSET;
+        for item in items {
+            serialize_offset_into(offset, buffer)?;
+            offset += item.serialized_len();
+        }
+    }
+
+    for item in items {
+        item.serialize_into(buffer)?;
+    }
+    Ok(())
+}
+
+/// Returns the number of bytes `serialize_items_into` appends for `items`.
+pub fn serialized_items_len<T: Serialize>(items: &[T]) -> usize {
+    if T::is_variable_size() {
+        items
+            .iter()
+            .map(|item| BYTES_PER_LENGTH_OFFSET + item.serialized_len())
+            .sum()
+    } else {
+        items.len() * T::fixed_length()
+    }
+}
+
+/// Reads the offset at `position`, checking that it is in bounds and not smaller than `previous`.
+pub(crate) fn read_offset(bytes: &[u8], position: usize, previous: usize) -> Result<usize, Error> {
+    let offset = match bytes.get(position..position + BYTES_PER_LENGTH_OFFSET) {
+        Some(offset_bytes) => deserialize_offset(offset_bytes),
         _ => Err(Error::InvalidByteLength {
-            required: BYTES_PER_LENGTH_OFFSET,
             got: bytes.len(),
+            required: position + BYTES_PER_LENGTH_OFFSET,
         }),
     }?;
 
-    let number_of_elements = first_offset / BYTES_PER_LENGTH_OFFSET;
+    if offset > bytes.len() {
+        Err(Error::OffsetOutOfBounds(offset))
+    } else if offset < previous {
+        Err(Error::OffsetOutOfOrder(offset))
+    } else {
+        Ok(offset)
+    }
+}
+
+/// Returns the number of elements in the bytes of a list of variable size elements.
+///
+/// The fixed part holds one offset per element, so the first offset gives the element count.
+pub(crate) fn variable_sized_items_count(bytes: &[u8]) -> Result<usize, Error> {
+    let first_offset = read_offset(bytes, 0, 0)?;
+    if first_offset < BYTES_PER_LENGTH_OFFSET {
+        return Err(Error::OffsetIntoFixedPortion(first_offset));
+    }
+    if !first_offset.is_multiple_of(BYTES_PER_LENGTH_OFFSET) {
+        return Err(Error::InvalidListFixedBytesLength(first_offset));
+    }
+
+    Ok(first_offset / BYTES_PER_LENGTH_OFFSET)
+}
+
+/// Returns the number of `T`s in the bytes of a list of them, without decoding any of them.
+pub(crate) fn items_count<T: SszType>(bytes: &[u8]) -> Result<usize, Error> {
+    if bytes.is_empty() {
+        Ok(0)
+    } else if T::is_variable_size() {
+        variable_sized_items_count(bytes)
+    } else {
+        // Zero sized items make any non-empty bytes invalid.
+        let item_length = T::fixed_length();
+        match bytes.len().checked_rem(item_length) {
+            Some(0) => Ok(bytes.len() / item_length),
+            _ => Err(Error::InvalidByteLength {
+                got: bytes.len(),
+                required: (bytes.len() / item_length.max(1) + 1) * item_length,
+            }),
+        }
+    }
+}
+
+pub fn deserialize_variable_sized_items<T: Deserialize>(bytes: &[u8]) -> Result<Vec<T>, Error> {
+    let number_of_elements = variable_sized_items_count(bytes)?;
+    let first_offset = number_of_elements * BYTES_PER_LENGTH_OFFSET;
     let mut result = Vec::with_capacity(number_of_elements);
 
     let mut previous_offset = first_offset;
@@ -41,35 +151,60 @@ pub fn deserialize_variable_sized_items<T: Deserialize>(bytes: &[u8]) -> Result<
         let next_offset = if i == number_of_elements {
             bytes.len()
         } else {
-            match bytes.get(i * BYTES_PER_LENGTH_OFFSET..(i + 1) * BYTES_PER_LENGTH_OFFSET) {
-                Some(bytes) => deserialize_offset(bytes),
-                _ => Err(Error::InvalidByteLength {
-                    required: (i + 1) * BYTES_PER_LENGTH_OFFSET,
-                    got: bytes.len(),
-                }),
-            }?
+            read_offset(bytes, i * BYTES_PER_LENGTH_OFFSET, previous_offset)?
         };
 
-        let element = match bytes.get(previous_offset..next_offset) {
-            Some(bytes) => T::deserialize(bytes),
-            _ => Err(Error::InvalidByteLength {
-                required: next_offset,
-                got: bytes.len(),
-            }),
-        }?;
-
-        result.push(element);
+        let item = T::deserialize(&bytes[previous_offset..next_offset])
+            .map_err(|error| error.in_item(i - 1, previous_offset))?;
+        result.push(item);
         previous_offset = next_offset;
     }
     Ok(result)
 }
 
+/// Deserializes the bytes of a list of at most `max_len` items.
+///
+/// The number of items is worked out from the length of the bytes, or from the first offset if
+/// the items are variable size, and checked against `max_len` before anything is allocated.
+pub fn deserialize_with_max<T: Deserialize>(bytes: &[u8], max_len: usize) -> Result<Vec<T>, Error> {
+    if bytes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let items_count = items_count::<T>(bytes)?;
+    if items_count > max_len {
+        return Err(Error::TooMuchElements {
+            got: items_count,
+            max: max_len,
+        });
+    }
+
+    if T::is_variable_size() {
+        deserialize_variable_sized_items(bytes)
+    } else {
+        let item_length = T::fixed_length();
+        let mut result = Vec::with_capacity(items_count);
+        for (index, chunk) in bytes.chunks(item_length).enumerate() {
+            let item =
+                T::deserialize(chunk).map_err(|error| error.in_item(index, index * item_length))?;
+            result.push(item);
+        }
+        Ok(result)
+    }
+}
+
+/// Splits the bytes of a container into its fields.
+///
+/// Every field is registered with `next_type` before any is read with `deserialize_next`. The
+/// first read checks that the fixed part ends where the first offset points, or at the end of the
+/// bytes if no field is variable size.
 pub struct Decoder<'a> {
     bytes: &'a [u8],
     registration_offset: usize,
     fixed_part_offset: usize,
     offsets: Vec<usize>,
     current_offset_index: usize,
+    fixed_part_checked: bool,
 }
 
 impl<'a> Decoder<'a> {
@@ -80,29 +215,95 @@ impl<'a> Decoder<'a> {
             fixed_part_offset: 0,
             offsets: vec![],
             current_offset_index: 0,
+            fixed_part_checked: false,
         }
     }
 
-    pub fn next_type<T: Deserialize>(&mut self) -> Result<(), Error> {
-        if T::is_variable_size() {
-            let offset = match self
-                .bytes
-                .get(self.registration_offset..self.registration_offset + BYTES_PER_LENGTH_OFFSET)
-            {
-                Some(bytes) => deserialize_offset(bytes),
-                _ => Err(Error::InvalidByteLength {
-                    got: self.bytes.len(),
-                    required: self.registration_offset + BYTES_PER_LENGTH_OFFSET,
-                }),
-            }?;
+    pub fn next_type<T: SszType>(&mut self) -> Result<(), Error> {
+        self.next_type_with(T::is_variable_size(), T::fixed_length())
+    }
+
+    /// Like `next_type`, for fields whose size is not described by an `SszType` impl, such as
+    /// fields with a `#[ssz(with = "...")]` codec.
+    pub fn next_type_with(
+        &mut self,
+        is_variable_size: bool,
+        fixed_length: usize,
+    ) -> Result<(), Error> {
+        if is_variable_size {
+            let previous_offset = self.offsets.last().copied().unwrap_or(0);
+            let offset = read_offset(self.bytes, self.registration_offset, previous_offset)?;
             self.offsets.push(offset);
         }
-        self.registration_offset += T::fixed_length();
+        self.registration_offset += fixed_length;
         Ok(())
     }
 
     pub fn deserialize_next<T: Deserialize>(&mut self) -> Result<T, Error> {
-        let result = if T::is_variable_size() {
+        self.deserialize_next_with(T::is_variable_size(), T::fixed_length(), T::deserialize)
+    }
+
+    /// Like `deserialize_next`, decoding the field's bytes with `deserialize`.
+    pub fn deserialize_next_with<T>(
+        &mut self,
+        is_variable_size: bool,
+        fixed_length: usize,
+        deserialize: impl FnOnce(&[u8]) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        deserialize(self.next_bytes(is_variable_size, fixed_length)?)
+    }
+
+    /// Like `deserialize_next`, marking errors from decoding the field as coming from the field
+    /// `name` of `container`.
+    pub fn deserialize_field<T: Deserialize>(
+        &mut self,
+        container: &'static str,
+        name: &'static str,
+    ) -> Result<T, Error> {
+        self.deserialize_field_with(
+            container,
+            name,
+            T::is_variable_size(),
+            T::fixed_length(),
+            T::deserialize,
+        )
+    }
+
+    /// Like `deserialize_field`, decoding the field's bytes with `deserialize`.
+    pub fn deserialize_field_with<T>(
+        &mut self,
+        container: &'static str,
+        name: &'static str,
+        is_variable_size: bool,
+        fixed_length: usize,
+        deserialize: impl FnOnce(&[u8]) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let (start, bytes) = self.next_field(is_variable_size, fixed_length)?;
+        deserialize(bytes).map_err(|error| error.in_field(container, name, start))
+    }
+
+    /// Returns the bytes of the next registered field without deserializing them.
+    pub fn next_bytes(
+        &mut self,
+        is_variable_size: bool,
+        fixed_length: usize,
+    ) -> Result<&'a [u8], Error> {
+        self.next_field(is_variable_size, fixed_length)
+            .map(|(_, bytes)| bytes)
+    }
+
+    /// Returns where the bytes of the next registered field start, along with the bytes.
+    fn next_field(
+        &mut self,
+        is_variable_size: bool,
+        fixed_length: usize,
+    ) -> Result<(usize, &'a [u8]), Error> {
+        if !self.fixed_part_checked {
+            self.check_fixed_part()?;
+            self.fixed_part_checked = true;
+        }
+
+        let (start, bytes) = if is_variable_size {
             let current_offset = match self.offsets.get(self.current_offset_index) {
                 Some(offset) => Ok(*offset),
                 _ => Err(Error::NoOffsetsLeft),
@@ -113,34 +314,45 @@ impl<'a> Decoder<'a> {
                 _ => self.bytes.len(),
             };
 
-            match self.bytes.get(current_offset..next_offset) {
-                Some(bytes) => T::deserialize(bytes),
-                _ => Err(Error::InvalidByteLength {
-                    got: self.bytes.len(),
-                    required: next_offset,
-                }),
-            }
+            (current_offset, &self.bytes[current_offset..next_offset])
         } else {
             match self
                 .bytes
-                .get(self.fixed_part_offset..self.fixed_part_offset + T::fixed_length())
+                .get(self.fixed_part_offset..self.fixed_part_offset + fixed_length)
             {
-                Some(bytes) => T::deserialize(bytes),
-                _ => Err(Error::InvalidByteLength {
-                    got: self.bytes.len(),
-                    required: self.fixed_part_offset + T::fixed_length(),
-                }),
+                Some(bytes) => (self.fixed_part_offset, bytes),
+                _ => {
+                    return Err(Error::InvalidByteLength {
+                        got: self.bytes.len(),
+                        required: self.fixed_part_offset + fixed_length,
+                    })
+                }
             }
         };
 
-        if result.is_ok() {
-            if T::is_variable_size() {
-                self.current_offset_index += 1;
-            }
-            self.fixed_part_offset += T::fixed_length();
+        if is_variable_size {
+            self.current_offset_index += 1;
         }
+        self.fixed_part_offset += fixed_length;
+
+        Ok((start, bytes))
+    }
 
-        result
+    fn check_fixed_part(&self) -> Result<(), Error> {
+        match self.offsets.first() {
+            Some(&offset) if offset < self.registration_offset => {
+                Err(Error::OffsetIntoFixedPortion(offset))
+            }
+            Some(&offset) if offset > self.registration_offset => {
+                Err(Error::OffsetSkipsFixedPortion(offset))
+            }
+            Some(_) => Ok(()),
+            None if self.bytes.len() != self.registration_offset => Err(Error::InvalidByteLength {
+                got: self.bytes.len(),
+                required: self.registration_offset,
+            }),
+            None => Ok(()),
+        }
     }
 }
 
@@ -228,6 +440,104 @@ mod tests {
         }
     }
 
+    mod decoder_offsets {
+        use super::*;
+
+        fn decode_two_vecs(bytes: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Error> {
+            let mut decoder = Decoder::for_bytes(bytes);
+            decoder.next_type::<Vec<u8>>()?;
+            decoder.next_type::<Vec<u8>>()?;
+            Ok((decoder.deserialize_next()?, decoder.deserialize_next()?))
+        }
+
+        #[test]
+        fn valid() {
+            assert_eq!(
+                decode_two_vecs(&[8, 0, 0, 0, 9, 0, 0, 0, 1, 2]).expect("Test"),
+                (vec![1], vec![2])
+            );
+        }
+
+        #[test]
+        fn into_fixed_portion() {
+            assert!(matches!(
+                decode_two_vecs(&[7, 0, 0, 0, 9, 0, 0, 0, 1, 2]),
+                Err(Error::OffsetIntoFixedPortion(7))
+            ));
+        }
+
+        #[test]
+        fn skips_fixed_portion() {
+            assert!(matches!(
+                decode_two_vecs(&[9, 0, 0, 0, 9, 0, 0, 0, 1, 2]),
+                Err(Error::OffsetSkipsFixedPortion(9))
+            ));
+        }
+
+        #[test]
+        fn out_of_order() {
+            assert!(matches!(
+                decode_two_vecs(&[9, 0, 0, 0, 8, 0, 0, 0, 1, 2]),
+                Err(Error::OffsetOutOfOrder(8))
+            ));
+        }
+
+        #[test]
+        fn out_of_bounds() {
+            assert!(matches!(
+                decode_two_vecs(&[8, 0, 0, 0, 11, 0, 0, 0, 1, 2]),
+                Err(Error::OffsetOutOfBounds(11))
+            ));
+        }
+
+        #[test]
+        fn excess_fixed_bytes() {
+            let mut decoder = Decoder::for_bytes(&[1, 2]);
+            decoder.next_type::<u8>().expect("Test");
+            assert!(matches!(
+                decoder.deserialize_next::<u8>(),
+                Err(Error::InvalidByteLength {
+                    required: 1,
+                    got: 2
+                })
+            ));
+        }
+    }
+
+    mod deserialize_with_max {
+        use super::*;
+
+        #[test]
+        fn within_max() {
+            let items: Vec<u16> = deserialize_with_max(&[1, 0, 2, 0], 2).expect("Test");
+            assert_eq!(items, vec![1, 2]);
+
+            let items: Vec<Vec<u8>> =
+                deserialize_with_max(&[8, 0, 0, 0, 9, 0, 0, 0, 1, 2], 2).expect("Test");
+            assert_eq!(items, vec![vec![1], vec![2]]);
+        }
+
+        #[test]
+        fn fixed_size_items_over_max() {
+            let result: Result<Vec<u16>, _> = deserialize_with_max(&[1, 0, 2, 0, 3, 0], 2);
+            assert!(matches!(
+                result,
+                Err(Error::TooMuchElements { got: 3, max: 2 })
+            ))
+        }
+
+        #[test]
+        fn variable_size_items_over_max() {
+            // Only the first offset is read: it claims three items without any of their bytes.
+            let result: Result<Vec<Vec<u8>>, _> =
+                deserialize_with_max(&[12, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 2);
+            assert!(matches!(
+                result,
+                Err(Error::TooMuchElements { got: 3, max: 2 })
+            ))
+        }
+    }
+
     mod deserialize_variable_sized_items {
         use super::*;
 
@@ -264,6 +574,33 @@ mod tests {
             assert!(result.is_err())
         }
 
+        #[test]
+        fn first_offset_into_fixed_portion() {
+            let result: Result<Vec<Vec<u8>>, _> = deserialize_variable_sized_items(&[0, 0, 0, 0]);
+            assert!(matches!(result, Err(Error::OffsetIntoFixedPortion(0))))
+        }
+
+        #[test]
+        fn first_offset_not_multiple_of_offset_size() {
+            let result: Result<Vec<Vec<u8>>, _> =
+                deserialize_variable_sized_items(&[5, 0, 0, 0, 0, 1]);
+            assert!(matches!(result, Err(Error::InvalidListFixedBytesLength(5))))
+        }
+
+        #[test]
+        fn offsets_out_of_order() {
+            let result: Result<Vec<Vec<u8>>, _> =
+                deserialize_variable_sized_items(&[8, 0, 0, 0, 6, 0, 0, 0, 1]);
+            assert!(matches!(result, Err(Error::OffsetOutOfOrder(6))))
+        }
+
+        #[test]
+        fn offsets_out_of_bounds() {
+            let result: Result<Vec<Vec<u8>>, _> =
+                deserialize_variable_sized_items(&[8, 0, 0, 0, 10, 0, 0, 0, 1]);
+            assert!(matches!(result, Err(Error::OffsetOutOfBounds(10))))
+        }
+
         #[test]
         fn bad_element_data() {
             let result: Result<Vec<Vec<u16>>, _> =
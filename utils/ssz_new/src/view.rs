@@ -0,0 +1,259 @@
+use crate::utils::{items_count, read_offset};
+use crate::*;
+use std::marker::PhantomData;
+
+/// The encoded bytes of a container, split into its fields without decoding any of them.
+///
+/// Views derived with `SszView` wrap one of these.
+#[derive(Clone, Debug)]
+pub struct ContainerView<'a> {
+    bytes: &'a [u8],
+    fields: Vec<&'a [u8]>,
+}
+
+impl<'a> ContainerView<'a> {
+    /// Splits `bytes` into fields laid out as `(is_variable_size, fixed_length)` pairs, checking
+    /// the offsets the same way `Decoder` does.
+    pub fn new(bytes: &'a [u8], layout: &[(bool, usize)]) -> Result<Self, Error> {
+        let mut decoder = Decoder::for_bytes(bytes);
+        for &(is_variable_size, fixed_length) in layout {
+            decoder.next_type_with(is_variable_size, fixed_length)?;
+        }
+
+        let fields = layout
+            .iter()
+            .map(|&(is_variable_size, fixed_length)| {
+                decoder.next_bytes(is_variable_size, fixed_length)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { bytes, fields })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns the bytes of the field at `index` in the layout.
+    ///
+    /// # Panics
+    ///
+    /// If the layout has `index` fields or fewer.
+    pub fn field(&self, index: usize) -> &'a [u8] {
+        self.fields[index]
+    }
+}
+
+/// The encoded bytes of a `T`, decoded only when asked for.
+pub struct View<'a, T> {
+    bytes: &'a [u8],
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> View<'a, T> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+// Implemented by hand, as deriving would require `T: Clone`.
+impl<'a, T> Clone for View<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for View<'a, T> {}
+
+impl<'a, T: Deserialize> View<'a, T> {
+    pub fn decode(&self) -> Result<T, Error> {
+        T::deserialize(self.bytes)
+    }
+}
+
+/// Collections encoded as an SSZ vector or list of `Item`s, whose items a `View` can decode one
+/// at a time.
+pub trait Sequence {
+    type Item: Deserialize;
+
+    /// Returns the most items the collection can hold, if it is bounded.
+    fn max_len() -> Option<usize> {
+        None
+    }
+
+    /// Returns the number of items, if the collection is a vector of a fixed length.
+    fn fixed_len() -> Option<usize> {
+        None
+    }
+}
+
+impl<T: Deserialize> Sequence for Vec<T> {
+    type Item = T;
+}
+
+impl<T: Deserialize, const N: usize> Sequence for [T; N] {
+    type Item = T;
+
+    fn fixed_len() -> Option<usize> {
+        Some(N)
+    }
+}
+
+impl<'a, S: Sequence> View<'a, S> {
+    /// Returns the number of items without decoding any of them.
+    ///
+    /// The count is checked against the bounds of `S` the same way decoding all of it does.
+    pub fn len(&self) -> Result<usize, Error> {
+        if let (Some(len), false) = (S::fixed_len(), S::Item::is_variable_size()) {
+            let required = len * S::Item::fixed_length();
+            if self.bytes.len() != required {
+                return Err(Error::InvalidByteLength {
+                    got: self.bytes.len(),
+                    required,
+                });
+            }
+            return Ok(len);
+        }
+
+        let items_count = items_count::<S::Item>(self.bytes)?;
+        let max = S::fixed_len().or_else(S::max_len).unwrap_or(usize::MAX);
+        if items_count > max {
+            return Err(Error::TooMuchElements {
+                got: items_count,
+                max,
+            });
+        }
+        match S::fixed_len() {
+            Some(len) if items_count != len => Err(Error::InvalidBytes(format!(
+                "Cannot parse a vector of {} items from {} items",
+                len, items_count
+            ))),
+            _ => Ok(items_count),
+        }
+    }
+
+    pub fn is_empty(&self) -> Result<bool, Error> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Decodes the item at `index`, or returns `None` if there are `index` items or fewer.
+    pub fn get(&self, index: usize) -> Result<Option<S::Item>, Error> {
+        let len = self.len()?;
+        if index >= len {
+            return Ok(None);
+        }
+
+        let item_bytes = if S::Item::is_variable_size() {
+            let first_offset = len * BYTES_PER_LENGTH_OFFSET;
+            let start = read_offset(self.bytes, index * BYTES_PER_LENGTH_OFFSET, first_offset)?;
+            let end = if index + 1 == len {
+                self.bytes.len()
+            } else {
+                read_offset(self.bytes, (index + 1) * BYTES_PER_LENGTH_OFFSET, start)?
+            };
+            &self.bytes[start..end]
+        } else {
+            let item_length = S::Item::fixed_length();
+            &self.bytes[index * item_length..][..item_length]
+        };
+
+        S::Item::deserialize(item_bytes).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn container() {
+        // u16, Vec<u8>, bool, Vec<u8>
+        let layout = [(false, 2), (true, 4), (false, 1), (true, 4)];
+        let bytes = [1, 0, 11, 0, 0, 0, 1, 13, 0, 0, 0, 2, 3, 4];
+        let view = ContainerView::new(&bytes, &layout).expect("Test");
+
+        assert_eq!(view.field(0), &[1, 0]);
+        assert_eq!(view.field(1), &[2, 3]);
+        assert_eq!(view.field(2), &[1]);
+        assert_eq!(view.field(3), &[4]);
+        assert_eq!(view.as_bytes(), &bytes);
+    }
+
+    #[test]
+    fn container_errors() {
+        let layout = [(false, 2), (true, 4)];
+
+        // first offset inside the fixed part
+        assert!(ContainerView::new(&[1, 0, 5, 0, 0, 0], &layout).is_err());
+        // offset past the end
+        assert!(ContainerView::new(&[1, 0, 9, 0, 0, 0], &layout).is_err());
+        // trailing bytes after a fixed size container
+        assert!(ContainerView::new(&[1, 0, 0], &[(false, 2)]).is_err());
+    }
+
+    #[test]
+    fn value() {
+        let view = View::<u16>::new(&[1, 2]);
+        assert_eq!(view.decode().expect("Test"), 0x0201);
+        assert!(View::<u16>::new(&[1]).decode().is_err());
+    }
+
+    #[test]
+    fn fixed_size_items() {
+        let view = View::<Vec<u16>>::new(&[1, 0, 2, 0, 3, 0]);
+        assert_eq!(view.len().expect("Test"), 3);
+        assert_eq!(view.get(0).expect("Test"), Some(1));
+        assert_eq!(view.get(2).expect("Test"), Some(3));
+        assert_eq!(view.get(3).expect("Test"), None);
+
+        assert!(View::<Vec<u16>>::new(&[1, 0, 2]).len().is_err());
+        assert!(View::<Vec<u16>>::new(&[]).is_empty().expect("Test"));
+    }
+
+    #[test]
+    fn variable_size_items() {
+        let view = View::<Vec<Vec<u8>>>::new(&[12, 0, 0, 0, 14, 0, 0, 0, 14, 0, 0, 0, 1, 2, 3]);
+        assert_eq!(view.len().expect("Test"), 3);
+        assert_eq!(view.get(0).expect("Test"), Some(vec![1, 2]));
+        assert_eq!(view.get(1).expect("Test"), Some(vec![]));
+        assert_eq!(view.get(2).expect("Test"), Some(vec![3]));
+        assert_eq!(view.get(3).expect("Test"), None);
+
+        // offsets out of order
+        let view = View::<Vec<Vec<u8>>>::new(&[8, 0, 0, 0, 7, 0, 0, 0, 1]);
+        assert!(view.get(0).is_err());
+    }
+
+    #[test]
+    fn fixed_len_items() {
+        assert_eq!(View::<[u16; 2]>::new(&[1, 0, 2, 0]).len().expect("Test"), 2);
+        assert!(matches!(
+            View::<[u16; 2]>::new(&[1, 0]).len(),
+            Err(Error::InvalidByteLength {
+                got: 2,
+                required: 4
+            })
+        ));
+
+        // two items where three are expected, and four where at most three fit
+        for bytes in [
+            vec![vec![1u8], vec![2]].serialize().expect("Test"),
+            vec![vec![1u8], vec![], vec![], vec![2]]
+                .serialize()
+                .expect("Test"),
+        ] {
+            let view = View::<[Vec<u8>; 3]>::new(&bytes);
+            assert_eq!(
+                view.len().unwrap_err().to_string(),
+                <[Vec<u8>; 3]>::deserialize(&bytes).unwrap_err().to_string()
+            );
+        }
+    }
+}
@@ -1,33 +1,155 @@
-use ssz::{Deserialize, Serialize};
-use ssz_derive::{SszDeserialize, SszSerialize};
+use std::marker::PhantomData;
 
-#[derive(SszSerialize, SszDeserialize, PartialEq, Debug)]
+use ssz::{Deserialize, Serialize, SszType};
+use ssz_derive::{SszDeserialize, SszSerialize, SszView};
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
 struct Fixed {
     a: u16,
     b: bool,
 }
 
-#[derive(SszSerialize, SszDeserialize, PartialEq, Debug)]
+#[derive(SszType, SszSerialize, SszDeserialize, SszView, PartialEq, Debug)]
 struct Variable {
     a: u16,
     b: Vec<u8>,
     c: bool,
 }
 
-#[derive(SszSerialize, SszDeserialize, PartialEq, Debug)]
+#[derive(SszType, SszSerialize, SszDeserialize, SszView, PartialEq, Debug)]
 struct Nested {
     fixed: Fixed,
     variable: Variable,
 }
 
+#[derive(SszType, SszSerialize, SszDeserialize, SszView, PartialEq, Debug)]
+struct Generic<T> {
+    a: u16,
+    b: Vec<T>,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+struct WithWhereClause<T, U>
+where
+    T: Copy,
+{
+    fixed: T,
+    variable: U,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+struct WithLifetime<'a, T: 'a> {
+    value: T,
+    #[ssz(skip_serializing)]
+    #[ssz(skip_deserializing)]
+    marker: PhantomData<&'a T>,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, SszView, PartialEq, Debug)]
+struct WithAttributes {
+    a: u16,
+    #[ssz(skip)]
+    cache: Vec<u8>,
+    #[ssz(with = "utf8")]
+    name: String,
+    #[ssz(with = "big_endian")]
+    port: u16,
+    b: bool,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+struct WithMaxLen<T> {
+    a: u8,
+    #[ssz(max_len = "2")]
+    b: Vec<T>,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+enum Union {
+    None,
+    Number(u16),
+    Bytes(Vec<u8>),
+    Fixed(Fixed),
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug)]
+struct WithUnion {
+    a: u8,
+    union: Union,
+    option: Option<u8>,
+}
+
+mod utf8 {
+    use ssz::{Deserialize, Error};
+
+    pub fn serialize_into(string: &str, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        buffer.extend_from_slice(string.as_bytes());
+        Ok(())
+    }
+
+    pub fn serialized_len(string: &str) -> usize {
+        string.len()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<String, Error> {
+        String::from_utf8(Vec::deserialize(bytes)?)
+            .map_err(|error| Error::InvalidBytes(error.to_string()))
+    }
+
+    pub fn is_variable_size() -> bool {
+        true
+    }
+
+    pub fn fixed_length() -> usize {
+        ssz::BYTES_PER_LENGTH_OFFSET
+    }
+}
+
+mod big_endian {
+    use ssz::Error;
+
+    pub fn serialize_into(value: &u16, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        buffer.extend_from_slice(&value.to_be_bytes());
+        Ok(())
+    }
+
+    pub fn serialized_len(_value: &u16) -> usize {
+        2
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<u16, Error> {
+        match bytes {
+            [high, low] => Ok(u16::from_be_bytes([*high, *low])),
+            _ => Err(Error::InvalidByteLength {
+                required: 2,
+                got: bytes.len(),
+            }),
+        }
+    }
+
+    pub fn is_variable_size() -> bool {
+        false
+    }
+
+    pub fn fixed_length() -> usize {
+        2
+    }
+}
+
 mod serialize_derive {
     use crate::*;
 
     #[test]
     fn is_fixed_size() {
-        assert!(<Nested as Serialize>::is_variable_size());
-        assert!(<Variable as Serialize>::is_variable_size());
-        assert!(!<Fixed as Serialize>::is_variable_size());
+        assert!(<Nested as SszType>::is_variable_size());
+        assert!(<Variable as SszType>::is_variable_size());
+        assert!(!<Fixed as SszType>::is_variable_size());
     }
 
     #[test]
@@ -80,6 +202,137 @@ mod serialize_derive {
             vec![5, 0, 0, 7, 0, 0, 0, 80, 0, 7, 0, 0, 0, 1, 1, 2, 3, 4]
         );
     }
+
+    #[test]
+    fn serialize_generic_struct() {
+        let generic = Generic {
+            a: 1,
+            b: vec![Fixed { a: 2, b: true }],
+        };
+
+        assert_eq!(
+            generic.serialize().unwrap(),
+            vec![1, 0, 6, 0, 0, 0, 2, 0, 1]
+        );
+        assert!(!<WithWhereClause<u8, u16> as SszType>::is_variable_size());
+        assert!(<WithWhereClause<u8, Vec<u8>> as SszType>::is_variable_size());
+    }
+
+    #[test]
+    fn serialize_struct_with_where_clause() {
+        let with_where_clause = WithWhereClause {
+            fixed: 3_u8,
+            variable: vec![4_u8, 5],
+        };
+
+        assert_eq!(
+            with_where_clause.serialize().unwrap(),
+            vec![3, 5, 0, 0, 0, 4, 5]
+        );
+    }
+
+    #[test]
+    fn serialize_struct_with_lifetime() {
+        let with_lifetime = WithLifetime {
+            value: 6_u32,
+            marker: PhantomData,
+        };
+
+        assert_eq!(with_lifetime.serialize().unwrap(), vec![6, 0, 0, 0]);
+    }
+
+    #[test]
+    fn serialize_union() {
+        assert!(<Union as SszType>::is_variable_size());
+        assert_eq!(Union::None.serialize().unwrap(), vec![0]);
+        assert_eq!(Union::Number(0x0102).serialize().unwrap(), vec![1, 2, 1]);
+        assert_eq!(Union::Bytes(vec![3, 4]).serialize().unwrap(), vec![2, 3, 4]);
+        assert_eq!(
+            Union::Fixed(Fixed { a: 5, b: true }).serialize().unwrap(),
+            vec![3, 5, 0, 1]
+        );
+        assert_eq!(
+            Either::<u8, Vec<u8>>::Right(vec![6]).serialize().unwrap(),
+            vec![1, 6]
+        );
+    }
+
+    #[test]
+    fn serialize_struct_with_union() {
+        let with_union = WithUnion {
+            a: 7,
+            union: Union::Number(8),
+            option: None,
+        };
+
+        assert_eq!(
+            with_union.serialize().unwrap(),
+            vec![7, 9, 0, 0, 0, 12, 0, 0, 0, 1, 8, 0, 0]
+        );
+    }
+
+    #[test]
+    fn serialize_into_appends() {
+        let nested = Nested {
+            fixed: Fixed { a: 5, b: false },
+            variable: Variable {
+                a: 80,
+                b: vec![1, 2, 3, 4],
+                c: true,
+            },
+        };
+        let mut buffer = vec![9, 9];
+
+        nested.serialize_into(&mut buffer).unwrap();
+        assert_eq!(buffer[..2], [9, 9]);
+        assert_eq!(buffer[2..], nested.serialize().unwrap()[..]);
+    }
+
+    #[test]
+    fn serialized_len_matches_serialize() {
+        let nested = Nested {
+            fixed: Fixed { a: 5, b: false },
+            variable: Variable {
+                a: 80,
+                b: vec![1, 2, 3, 4],
+                c: true,
+            },
+        };
+        let with_union = WithUnion {
+            a: 7,
+            union: Union::Bytes(vec![1, 2, 3]),
+            option: Some(4),
+        };
+        let with_attributes = WithAttributes {
+            a: 1,
+            cache: vec![5, 6, 7],
+            name: "abc".to_string(),
+            port: 0x0102,
+            b: true,
+        };
+
+        assert_eq!(nested.serialized_len(), 18);
+        assert_eq!(with_union.serialized_len(), 15);
+        assert_eq!(with_attributes.serialized_len(), 12);
+        assert_eq!(Union::None.serialized_len(), 1);
+    }
+
+    #[test]
+    fn serialize_struct_with_attributes() {
+        let with_attributes = WithAttributes {
+            a: 1,
+            cache: vec![5, 6, 7],
+            name: "ab".to_string(),
+            port: 0x0102,
+            b: true,
+        };
+
+        assert!(<WithAttributes as SszType>::is_variable_size());
+        assert_eq!(
+            with_attributes.serialize().unwrap(),
+            vec![1, 0, 9, 0, 0, 0, 1, 2, 1, 97, 98]
+        );
+    }
 }
 
 mod deserialize_derive {
@@ -136,4 +389,220 @@ mod deserialize_derive {
             nested
         );
     }
+
+    #[test]
+    fn deserialize_generic_struct() {
+        let generic = Generic {
+            a: 1,
+            b: vec![Fixed { a: 2, b: true }],
+        };
+
+        assert_eq!(
+            Generic::deserialize(&[1, 0, 6, 0, 0, 0, 2, 0, 1]).unwrap(),
+            generic
+        );
+        assert_eq!(<WithWhereClause<u8, u16> as SszType>::fixed_length(), 3);
+        assert_eq!(
+            <WithWhereClause<u8, Vec<u8>> as SszType>::fixed_length(),
+            ssz::BYTES_PER_LENGTH_OFFSET
+        );
+    }
+
+    #[test]
+    fn deserialize_struct_with_where_clause() {
+        let with_where_clause = WithWhereClause {
+            fixed: 3_u8,
+            variable: vec![4_u8, 5],
+        };
+
+        assert_eq!(
+            WithWhereClause::deserialize(&[3, 5, 0, 0, 0, 4, 5]).unwrap(),
+            with_where_clause
+        );
+    }
+
+    #[test]
+    fn deserialize_struct_with_lifetime() {
+        let with_lifetime = WithLifetime {
+            value: 6_u32,
+            marker: PhantomData,
+        };
+
+        assert_eq!(
+            WithLifetime::deserialize(&[6, 0, 0, 0]).unwrap(),
+            with_lifetime
+        );
+    }
+
+    #[test]
+    fn deserialize_union() {
+        assert_eq!(
+            <Union as SszType>::fixed_length(),
+            ssz::BYTES_PER_LENGTH_OFFSET
+        );
+        assert_eq!(Union::deserialize(&[0]).unwrap(), Union::None);
+        assert_eq!(
+            Union::deserialize(&[1, 2, 1]).unwrap(),
+            Union::Number(0x0102)
+        );
+        assert_eq!(
+            Union::deserialize(&[2, 3, 4]).unwrap(),
+            Union::Bytes(vec![3, 4])
+        );
+        assert_eq!(
+            Union::deserialize(&[3, 5, 0, 1]).unwrap(),
+            Union::Fixed(Fixed { a: 5, b: true })
+        );
+        assert_eq!(
+            Either::<u8, Vec<u8>>::deserialize(&[1, 6]).unwrap(),
+            Either::Right(vec![6])
+        );
+    }
+
+    #[test]
+    fn deserialize_union_error() {
+        // missing selector
+        assert!(Union::deserialize(&[]).is_err());
+
+        // bytes after `None`
+        assert!(Union::deserialize(&[0, 1]).is_err());
+
+        // selector without a variant
+        assert!(Union::deserialize(&[4, 1]).is_err());
+
+        // wrong bytes to deserialize value
+        assert!(Union::deserialize(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn deserialize_struct_with_union() {
+        let with_union = WithUnion {
+            a: 7,
+            union: Union::Number(8),
+            option: None,
+        };
+
+        assert_eq!(
+            WithUnion::deserialize(&[7, 9, 0, 0, 0, 12, 0, 0, 0, 1, 8, 0, 0]).unwrap(),
+            with_union
+        );
+    }
+
+    #[test]
+    fn deserialize_struct_with_attributes() {
+        let with_attributes = WithAttributes {
+            a: 1,
+            cache: vec![],
+            name: "ab".to_string(),
+            port: 0x0102,
+            b: true,
+        };
+
+        assert_eq!(
+            WithAttributes::deserialize(&[1, 0, 9, 0, 0, 0, 1, 2, 1, 97, 98]).unwrap(),
+            with_attributes
+        );
+        assert!(WithAttributes::deserialize(&[1, 0, 9, 0, 0, 0, 1, 2, 1, 0xff]).is_err());
+    }
+
+    #[test]
+    fn deserialize_struct_with_max_len() {
+        let with_max_len = WithMaxLen {
+            a: 1,
+            b: vec![2u16, 3],
+        };
+        let bytes = with_max_len.serialize().unwrap();
+        assert_eq!(WithMaxLen::deserialize(&bytes).unwrap(), with_max_len);
+
+        let too_long = WithMaxLen {
+            a: 1,
+            b: vec![2u16, 3, 4],
+        };
+        let error = WithMaxLen::<u16>::deserialize(&too_long.serialize().unwrap()).unwrap_err();
+        assert!(matches!(
+            error.innermost(),
+            ssz::Error::TooMuchElements { got: 3, max: 2 }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "WithMaxLen.b (at byte 5): expected at most 2 elements, got 3"
+        );
+    }
+
+    #[test]
+    fn deserialize_error_path() {
+        let generic = Generic {
+            a: 1,
+            b: vec![
+                Variable {
+                    a: 2,
+                    b: vec![],
+                    c: true,
+                },
+                Variable {
+                    a: 3,
+                    b: vec![],
+                    c: true,
+                },
+            ],
+        };
+        let mut bytes = generic.serialize().unwrap();
+        // `c` of the second item: 6 bytes of `Generic`, 8 bytes of offsets and 7 bytes of the
+        // first item before the second one, whose `c` follows `a` and the offset of `b`.
+        bytes[27] = 2;
+
+        let error = Generic::<Variable>::deserialize(&bytes).unwrap_err();
+        assert!(matches!(error.innermost(), ssz::Error::InvalidBytes(_)));
+        assert_eq!(
+            error.to_string(),
+            "Generic.b[1].c (at byte 27): invalid bytes: Cannot deserialize bool from 2"
+        );
+    }
+}
+
+mod view_derive {
+    use crate::*;
+
+    #[test]
+    fn read_fields() {
+        let bytes = [1, 0, 1, 7, 0, 0, 0, 5, 0, 7, 0, 0, 0, 0, 7, 8, 9];
+        let view = NestedView::new(&bytes).unwrap();
+
+        assert_eq!(view.fixed().decode().unwrap(), Fixed { a: 1, b: true });
+        assert_eq!(view.as_bytes(), &bytes);
+
+        let variable = VariableView::new(view.variable().as_bytes()).unwrap();
+        assert_eq!(variable.a().decode().unwrap(), 5);
+        assert_eq!(variable.b().len().unwrap(), 3);
+        assert_eq!(variable.b().get(1).unwrap(), Some(8));
+        assert_eq!(variable.b().get(3).unwrap(), None);
+        assert!(!variable.c().decode().unwrap());
+    }
+
+    #[test]
+    fn read_generic_fields() {
+        let bytes = [1, 0, 6, 0, 0, 0, 2, 0, 3, 0];
+        let view = GenericView::<u16>::new(&bytes).unwrap();
+
+        assert_eq!(view.a().decode().unwrap(), 1);
+        assert_eq!(view.b().decode().unwrap(), vec![2, 3]);
+    }
+
+    #[test]
+    fn read_fields_with_attributes() {
+        let view = WithAttributesView::new(&[1, 0, 9, 0, 0, 0, 1, 2, 1, 97, 98]).unwrap();
+
+        assert_eq!(view.a().decode().unwrap(), 1);
+        assert_eq!(view.name().unwrap(), "ab");
+        assert_eq!(view.port().unwrap(), 0x0102);
+        assert!(view.b().decode().unwrap());
+    }
+
+    #[test]
+    fn invalid_offsets() {
+        // the offset of `variable` points into the fixed part
+        assert!(NestedView::new(&[1, 0, 1, 6, 0, 0, 0, 5, 0, 7, 0, 0, 0, 0, 7, 8, 9]).is_err());
+        // the offset of `b` points past the end
+        assert!(VariableView::new(&[5, 0, 20, 0, 0, 0, 0]).is_err());
+    }
 }
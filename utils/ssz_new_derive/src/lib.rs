@@ -3,160 +3,729 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::DeriveInput;
+use proc_macro2::TokenTree;
+use quote::{quote, ToTokens};
+use syn::{parse_quote, DeriveInput, Lit, Meta, NestedMeta};
 
-#[proc_macro_derive(SszSerialize)]
-pub fn serialize_derive(input: TokenStream) -> TokenStream {
-    let ast: DeriveInput = syn::parse(input).expect("AST should be correct");
+/// Options set with `#[ssz(...)]` on the container.
+struct ContainerAttributes {
+    krate: syn::Path,
+}
 
-    let name = &ast.ident;
-    let fields = match &ast.data {
-        syn::Data::Struct(struct_data) => &struct_data.fields,
-        _ => panic!("Serialization only available for structs"),
+impl ContainerAttributes {
+    /// `krate` defaults to `ssz`, the crate's library name. Crates that depend on both `eth2_ssz`
+    /// and `ssz_new` can point the derive elsewhere with `#[ssz(crate = "ssz_new")]`.
+    fn parse(ast: &DeriveInput) -> Result<Self, syn::Error> {
+        let mut krate = parse_quote!(ssz);
+
+        for item in ssz_meta_items(&ast.attrs)? {
+            match &item {
+                NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.ident == "crate" => {
+                    krate = parse_string_literal(&name_value.lit)?;
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        item,
+                        "unknown ssz container attribute",
+                    ))
+                }
+            }
+        }
+
+        Ok(Self { krate })
+    }
+}
+
+/// Options set with `#[ssz(...)]` on a field.
+#[derive(Default)]
+struct FieldAttributes {
+    skip_serializing: bool,
+    skip_deserializing: bool,
+    with: Option<syn::Path>,
+    max_len: Option<syn::Expr>,
+}
+
+impl FieldAttributes {
+    /// Besides `skip`, `with` and `max_len`, understands the `skip_serializing` and
+    /// `skip_deserializing` attributes of the legacy `ssz_derive`, so containers deriving both
+    /// need no extra annotations.
+    fn parse(field: &syn::Field) -> Result<Self, syn::Error> {
+        let mut attributes = Self::default();
+
+        for item in ssz_meta_items(&field.attrs)? {
+            match &item {
+                NestedMeta::Meta(Meta::Word(word)) if word == "skip" => {
+                    attributes.skip_serializing = true;
+                    attributes.skip_deserializing = true;
+                }
+                NestedMeta::Meta(Meta::Word(word)) if word == "skip_serializing" => {
+                    attributes.skip_serializing = true;
+                }
+                NestedMeta::Meta(Meta::Word(word)) if word == "skip_deserializing" => {
+                    attributes.skip_deserializing = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.ident == "with" => {
+                    attributes.with = Some(parse_string_literal(&name_value.lit)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.ident == "max_len" => {
+                    attributes.max_len = Some(parse_string_literal(&name_value.lit)?);
+                }
+                _ => return Err(syn::Error::new_spanned(item, "unknown ssz field attribute")),
+            }
+        }
+
+        if attributes.with.is_some() && attributes.skip_serializing && attributes.skip_deserializing
+        {
+            return Err(syn::Error::new_spanned(
+                field,
+                "a skipped field cannot have a custom codec",
+            ));
+        }
+        if attributes.with.is_some() && attributes.max_len.is_some() {
+            return Err(syn::Error::new_spanned(
+                field,
+                "a field with a custom codec cannot have a maximum length",
+            ));
+        }
+        if attributes.max_len.is_some() && vec_item_type(&field.ty).is_none() {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "only `Vec` fields can have a maximum length",
+            ));
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// Collects the items of every `#[ssz(...)]` attribute in `attrs`.
+fn ssz_meta_items(attrs: &[syn::Attribute]) -> Result<Vec<NestedMeta>, syn::Error> {
+    let mut items = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("ssz")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => items.extend(list.nested),
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[ssz(...)]")),
+        }
+    }
+    Ok(items)
+}
+
+fn parse_string_literal<T: syn::parse::Parse>(lit: &Lit) -> Result<T, syn::Error> {
+    match lit {
+        Lit::Str(string) => string.parse(),
+        _ => Err(syn::Error::new_spanned(lit, "expected a string literal")),
+    }
+}
+
+/// Returns `T` if `ty` is `Vec<T>`.
+fn vec_item_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let segment = match ty {
+        syn::Type::Path(path) if path.qself.is_none() => path.path.segments.last()?.into_value(),
+        _ => return None,
     };
-    let fields_count = fields.iter().len();
-
-    let mut fixed_parts_pushes = Vec::with_capacity(fields_count);
-    let mut variable_parts_pushes = Vec::with_capacity(fields_count);
-    let mut is_variable_sizes = Vec::with_capacity(fields_count);
-    for field in fields {
-        let field_type = &field.ty;
-        let field_name = match &field.ident {
-            Some(ident) => ident,
-            _ => panic!("All fields must have names"),
-        };
-
-        fixed_parts_pushes.push(quote! {
-            fixed_parts.push(if !<#field_type as ssz::Serialize>::is_variable_size() {
-                Some(self.#field_name.serialize()?)
-            } else {
-                None
-            });
-        });
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(arguments)
+            if segment.ident == "Vec" && arguments.args.len() == 1 =>
+        {
+            match &arguments.args[0] {
+                syn::GenericArgument::Type(item_type) => Some(item_type),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
 
-        variable_parts_pushes.push(quote! {
-            variable_parts.push(if <#field_type as ssz::Serialize>::is_variable_size() {
-                self.#field_name.serialize()?
-            } else {
-                vec![]
-            });
-        });
+/// A named field of the container together with its attributes.
+struct Field<'a> {
+    name: &'a syn::Ident,
+    vis: &'a syn::Visibility,
+    ty: &'a syn::Type,
+    attributes: FieldAttributes,
+}
 
-        is_variable_sizes.push(quote! {
-            <#field_type as ssz::Serialize>::is_variable_size()
-        });
+fn parse_fields(fields: &syn::Fields) -> Result<Vec<Field<'_>>, syn::Error> {
+    fields
+        .iter()
+        .map(|field| {
+            let name = field.ident.as_ref().ok_or_else(|| {
+                syn::Error::new_spanned(field, "ssz_new_derive only supports named fields")
+            })?;
+            Ok(Field {
+                name,
+                vis: &field.vis,
+                ty: &field.ty,
+                attributes: FieldAttributes::parse(field)?,
+            })
+        })
+        .collect()
+}
+
+impl Field<'_> {
+    /// Returns expressions for the field's `SszType::is_variable_size` and `SszType::fixed_length`,
+    /// taken from its `with` module if it has one.
+    fn layout(&self, krate: &syn::Path) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let ty = self.ty;
+        match &self.attributes.with {
+            Some(module) => (
+                quote! { #module::is_variable_size() },
+                quote! { #module::fixed_length() },
+            ),
+            None => (
+                quote! { <#ty as #krate::SszType>::is_variable_size() },
+                quote! { <#ty as #krate::SszType>::fixed_length() },
+            ),
+        }
+    }
+}
+
+/// A variant of an enum deriving a union: `Variant(T)`, or `Variant` for the `None` option.
+struct Variant<'a> {
+    name: &'a syn::Ident,
+    selector: u8,
+    ty: Option<&'a syn::Type>,
+}
+
+/// Selectors are the variants' positions, so unions can have at most 128 variants.
+const MAX_UNION_VARIANTS: usize = 128;
+
+fn parse_variants<'a>(
+    name: &syn::Ident,
+    enum_data: &'a syn::DataEnum,
+) -> Result<Vec<Variant<'a>>, syn::Error> {
+    if enum_data.variants.len() > MAX_UNION_VARIANTS {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("a union can have at most {} variants", MAX_UNION_VARIANTS),
+        ));
+    }
+
+    enum_data
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(index, variant)| {
+            if let Some((_, discriminant)) = &variant.discriminant {
+                return Err(syn::Error::new_spanned(
+                    discriminant,
+                    "the union selector is the variant's position and cannot be set explicitly",
+                ));
+            }
+            if let Some(attr) = variant
+                .attrs
+                .iter()
+                .chain(variant.fields.iter().flat_map(|field| &field.attrs))
+                .find(|attr| attr.path.is_ident("ssz"))
+            {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "ssz attributes are not supported on union variants",
+                ));
+            }
+
+            let ty = match &variant.fields {
+                syn::Fields::Unit if index == 0 => None,
+                syn::Fields::Unit => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "only the first variant of a union can be empty",
+                    ))
+                }
+                syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                    Some(&fields.unnamed[0].ty)
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        variant,
+                        "union variants must hold a single unnamed value",
+                    ))
+                }
+            };
+
+            Ok(Variant {
+                name: &variant.ident,
+                selector: index as u8,
+                ty,
+            })
+        })
+        .collect()
+}
+
+fn unsupported_data(ast: &DeriveInput) -> syn::Error {
+    syn::Error::new_spanned(&ast.ident, "ssz_new_derive only supports structs and enums")
+}
+
+/// Returns a copy of `generics` with a `<field type>: <bound>` predicate for every field whose type
+/// mentions one of the type parameters.
+///
+/// Fields with concrete types need no predicate, and leaving them out keeps private field types
+/// out of the impl's signature.
+fn bounded_generics<'a>(
+    generics: &syn::Generics,
+    field_types: impl Iterator<Item = &'a syn::Type>,
+    bound: syn::Path,
+) -> syn::Generics {
+    let type_params = generics
+        .type_params()
+        .map(|param| &param.ident)
+        .collect::<Vec<_>>();
+
+    let mut bounded = generics.clone();
+    for field_type in field_types {
+        if mentions_any(field_type.into_token_stream(), &type_params) {
+            bounded
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#field_type: #bound));
+        }
     }
+    bounded
+}
+
+fn mentions_any(tokens: proc_macro2::TokenStream, idents: &[&syn::Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => idents.iter().any(|param| **param == ident),
+        TokenTree::Group(group) => mentions_any(group.stream(), idents),
+        _ => false,
+    })
+}
 
-    let generated = quote! {
-        impl ssz::Serialize for #name {
-            fn serialize(&self) -> Result<Vec<u8>, ssz::Error> {
-                let fields_count = #fields_count;
+/// Implements `ssz::SszType` for a struct with named fields: it is variable size if any of its
+/// serialized fields is, and otherwise as long as all of them together.
+///
+/// Enums are SSZ unions, which are always variable size.
+///
+/// ## Field attributes
+///
+/// - `#[ssz(skip)]`: the field is not part of the layout. The legacy `skip_serializing` and
+///   `skip_deserializing` attributes must be used together.
+/// - `#[ssz(with = "module")]`: the field's layout is given by `module::is_variable_size()` and
+///   `module::fixed_length()`.
+#[proc_macro_derive(SszType, attributes(ssz))]
+pub fn ssz_type_derive(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(input).expect("AST should be correct");
+
+    let generated = match &ast.data {
+        syn::Data::Struct(struct_data) => expand_struct_ssz_type(&ast, &struct_data.fields),
+        syn::Data::Enum(enum_data) => expand_union_ssz_type(&ast, enum_data),
+        syn::Data::Union(_) => Err(unsupported_data(&ast)),
+    };
+
+    match generated {
+        Ok(generated) => generated.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
 
-                let mut fixed_parts = Vec::with_capacity(fields_count);
+fn expand_struct_ssz_type(
+    ast: &DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let mut fields = parse_fields(fields)?;
+
+    // Serialization and deserialization share the layout, so they must agree on the fields in it.
+    if let Some(field) = fields
+        .iter()
+        .find(|field| field.attributes.skip_serializing != field.attributes.skip_deserializing)
+    {
+        return Err(syn::Error::new_spanned(
+            field.name,
+            "a field must be skipped both when serializing and deserializing, use #[ssz(skip)]",
+        ));
+    }
+    fields.retain(|field| !field.attributes.skip_serializing);
+
+    let generics = bounded_generics(
+        &ast.generics,
+        fields
+            .iter()
+            .filter(|field| field.attributes.with.is_none())
+            .map(|field| field.ty),
+        parse_quote!(#krate::SszType),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (is_variable_sizes, fixed_lengths): (Vec<_>, Vec<_>) =
+        fields.iter().map(|field| field.layout(&krate)).unzip();
+
+    Ok(quote! {
+        impl #impl_generics #krate::SszType for #name #ty_generics #where_clause {
+            fn is_variable_size() -> bool {
                 #(
-                    #fixed_parts_pushes
+                    #is_variable_sizes ||
                 )*
+                    false
+            }
+
+            fn fixed_length() -> usize {
+                if <Self as #krate::SszType>::is_variable_size() {
+                    return #krate::BYTES_PER_LENGTH_OFFSET;
+                }
 
-                let mut variable_parts = Vec::with_capacity(fields_count);
                 #(
-                    #variable_parts_pushes
+                    #fixed_lengths +
                 )*
+                    0
+            }
+        }
+    })
+}
 
-                let fixed_length: usize = fixed_parts.iter()
-                    .map(|part| match part {
-                        Some(bytes) => bytes.len(),
-                        None => ssz::BYTES_PER_LENGTH_OFFSET
-                    }).sum();
+fn expand_union_ssz_type(
+    ast: &DeriveInput,
+    enum_data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    parse_variants(name, enum_data)?;
 
-                let variable_lengths: Vec<usize> = variable_parts.iter()
-                    .map(|part| part.len())
-                    .collect();
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
 
-                let mut variable_offsets = Vec::with_capacity(fields_count);
-                for i in 0..fields_count {
-                    let variable_length_sum: usize = variable_lengths[..i].iter().sum();
-                    let offset = fixed_length + variable_length_sum;
-                    variable_offsets.push(ssz::serialize_offset(offset)?);
-                }
+    Ok(quote! {
+        impl #impl_generics #krate::SszType for #name #ty_generics #where_clause {
+            fn is_variable_size() -> bool {
+                true
+            }
 
-                let fixed_parts: Vec<&Vec<u8>> = fixed_parts.iter()
-                    .enumerate()
-                    .map(|(i, part)| match part {
-                        Some(bytes) => bytes,
-                        None => &variable_offsets[i]
-                    }).collect();
+            fn fixed_length() -> usize {
+                #krate::BYTES_PER_LENGTH_OFFSET
+            }
+        }
+    })
+}
 
-                let variable_lengths_sum: usize = variable_lengths.iter().sum();
-                let total_bytes = fixed_length + variable_lengths_sum;
-                let mut result = Vec::with_capacity(total_bytes);
+/// Implements `ssz::Serialize` for a struct with named fields, in the order they are defined.
+///
+/// Enums are serialized as SSZ unions: the position of the variant as a one-byte selector,
+/// followed by the value it holds. Every variant must hold a single value, except for the first,
+/// which can be empty to stand for `None`.
+///
+/// The layout comes from the type's `ssz::SszType` impl, usually derived with `SszType`.
+///
+/// ## Field attributes
+///
+/// - `#[ssz(skip)]` or `#[ssz(skip_serializing)]`: the field is not serialized.
+/// - `#[ssz(with = "module")]`: the field is serialized with
+///   `module::serialize_into(&T, &mut Vec<u8>)` and `module::serialized_len(&T)` instead of its
+///   `Serialize` impl.
+#[proc_macro_derive(SszSerialize, attributes(ssz))]
+pub fn serialize_derive(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(input).expect("AST should be correct");
 
-                for part in fixed_parts {
-                    result.extend(part);
-                }
+    let generated = match &ast.data {
+        syn::Data::Struct(struct_data) => expand_struct_serialize(&ast, &struct_data.fields),
+        syn::Data::Enum(enum_data) => expand_union_serialize(&ast, enum_data),
+        syn::Data::Union(_) => Err(unsupported_data(&ast)),
+    };
 
-                for part in variable_parts {
-                    result.extend(part);
-                }
+    match generated {
+        Ok(generated) => generated.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
 
-                Ok(result)
+fn expand_struct_serialize(
+    ast: &DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let fields = parse_fields(fields)?
+        .into_iter()
+        .filter(|field| !field.attributes.skip_serializing)
+        .collect::<Vec<_>>();
+
+    let generics = bounded_generics(
+        &ast.generics,
+        fields
+            .iter()
+            .filter(|field| field.attributes.with.is_none())
+            .map(|field| field.ty),
+        parse_quote!(#krate::Serialize),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut serialize_intos = Vec::with_capacity(fields.len());
+    let mut serialized_lens = Vec::with_capacity(fields.len());
+    for field in &fields {
+        let field_type = field.ty;
+        let field_name = field.name;
+
+        match &field.attributes.with {
+            Some(module) => {
+                serialize_intos.push(quote! { #module::serialize_into(&self.#field_name, buffer) });
+                serialized_lens.push(quote! { #module::serialized_len(&self.#field_name) });
+            }
+            None => {
+                serialize_intos.push(quote! {
+                    <#field_type as #krate::Serialize>::serialize_into(&self.#field_name, buffer)
+                });
+                serialized_lens.push(quote! {
+                    <#field_type as #krate::Serialize>::serialized_len(&self.#field_name)
+                });
             }
+        }
+    }
+    let (is_variable_sizes, fixed_lengths): (Vec<_>, Vec<_>) =
+        fields.iter().map(|field| field.layout(&krate)).unzip();
+
+    // Each field contributes its bytes to the fixed part if it is fixed size, and an offset to the
+    // fixed part and its bytes to the variable part otherwise.
+    let field_lens =
+        serialized_lens
+            .iter()
+            .zip(&is_variable_sizes)
+            .map(|(serialized_len, is_variable_size)| {
+                quote! {
+                    if #is_variable_size {
+                        #krate::BYTES_PER_LENGTH_OFFSET + #serialized_len
+                    } else {
+                        #serialized_len
+                    }
+                }
+            });
+    let fixed_part_pushes = serialize_intos
+        .iter()
+        .zip(&serialized_lens)
+        .zip(&is_variable_sizes)
+        .map(|((serialize_into, serialized_len), is_variable_size)| {
+            quote! {
+                if #is_variable_size {
+                    #krate::serialize_offset_into(variable_offset, buffer)?;
+                    variable_offset += #serialized_len;
+                } else {
+                    #serialize_into?;
+                }
+            }
+        });
+    let variable_part_pushes =
+        serialize_intos
+            .iter()
+            .zip(&is_variable_sizes)
+            .map(|(serialize_into, is_variable_size)| {
+                quote! {
+                    if #is_variable_size {
+                        #serialize_into?;
+                    }
+                }
+            });
+
+    Ok(quote! {
+        impl #impl_generics #krate::Serialize for #name #ty_generics #where_clause {
+            #[allow(unused_assignments, unused_mut, unused_variables)]
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), #krate::Error> {
+                let fixed_length: usize = 0 #( + #fixed_lengths )*;
+                let mut variable_offset = fixed_length;
 
-            fn is_variable_size() -> bool {
                 #(
-                    #is_variable_sizes ||
+                    #fixed_part_pushes
                 )*
-                    false
+
+                #(
+                    #variable_part_pushes
+                )*
+
+                Ok(())
+            }
+
+            fn serialized_len(&self) -> usize {
+                0 #( + #field_lens )*
             }
         }
-    };
+    })
+}
 
-    generated.into()
+fn expand_union_serialize(
+    ast: &DeriveInput,
+    enum_data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let variants = parse_variants(name, enum_data)?;
+
+    let generics = bounded_generics(
+        &ast.generics,
+        variants.iter().filter_map(|variant| variant.ty),
+        parse_quote!(#krate::Serialize),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let serialize_arms = variants.iter().map(|variant| {
+        let variant_name = variant.name;
+        let selector = variant.selector;
+        match variant.ty {
+            Some(ty) => quote! {
+                #name::#variant_name(value) => {
+                    #krate::serialize_union_selector_into(#selector, buffer)?;
+                    <#ty as #krate::Serialize>::serialize_into(value, buffer)
+                }
+            },
+            None => quote! {
+                #name::#variant_name => #krate::serialize_union_selector_into(#selector, buffer)
+            },
+        }
+    });
+    let len_arms = variants.iter().map(|variant| {
+        let variant_name = variant.name;
+        match variant.ty {
+            Some(ty) => quote! {
+                #name::#variant_name(value) => 1 + <#ty as #krate::Serialize>::serialized_len(value)
+            },
+            None => quote! {
+                #name::#variant_name => 1
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::Serialize for #name #ty_generics #where_clause {
+            fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), #krate::Error> {
+                match self {
+                    #(
+                        #serialize_arms,
+                    )*
+                }
+            }
+
+            fn serialized_len(&self) -> usize {
+                match self {
+                    #(
+                        #len_arms,
+                    )*
+                }
+            }
+        }
+    })
 }
 
-#[proc_macro_derive(SszDeserialize)]
+/// Implements `ssz::Deserialize` for a struct with named fields, in the order they are defined.
+///
+/// Errors from decoding a field are returned as `ssz::Error::Nested`, naming the struct, the path
+/// to the field and where its bytes start.
+///
+/// Enums are deserialized as SSZ unions, see `SszSerialize`.
+///
+/// ## Field attributes
+///
+/// - `#[ssz(skip)]` or `#[ssz(skip_deserializing)]`: the field is not read from the bytes and is
+///   set to its `Default` value instead.
+/// - `#[ssz(with = "module")]`: the field is deserialized with `module::deserialize(&[u8])`
+///   instead of its `Deserialize` impl, using `module::is_variable_size()` and
+///   `module::fixed_length()` to find its bytes.
+/// - `#[ssz(max_len = "expr")]`: the `Vec` field is deserialized with `ssz::deserialize_with_max`,
+///   rejecting more than `expr` items before allocating them.
+#[proc_macro_derive(SszDeserialize, attributes(ssz))]
 pub fn deserialize_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = syn::parse(input).expect("AST should be correct");
 
-    let name = &ast.ident;
-    let fields = match &ast.data {
-        syn::Data::Struct(struct_data) => &struct_data.fields,
-        _ => panic!("Deserialization only available for structs"),
+    let generated = match &ast.data {
+        syn::Data::Struct(struct_data) => expand_struct_deserialize(&ast, &struct_data.fields),
+        syn::Data::Enum(enum_data) => expand_union_deserialize(&ast, enum_data),
+        syn::Data::Union(_) => Err(unsupported_data(&ast)),
     };
-    let fields_count = fields.iter().len();
 
+    match generated {
+        Ok(generated) => generated.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_struct_deserialize(
+    ast: &DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let fields = parse_fields(fields)?;
+    let fields_count = fields.len();
+
+    // A `Vec` with a maximum length is decoded item by item, so the items need the bound.
+    let generics = bounded_generics(
+        &ast.generics,
+        fields
+            .iter()
+            .filter(|field| !field.attributes.skip_deserializing && field.attributes.with.is_none())
+            .map(|field| match field.attributes.max_len {
+                Some(_) => vec_item_type(field.ty).expect("checked when parsing attributes"),
+                None => field.ty,
+            }),
+        parse_quote!(#krate::Deserialize),
+    );
+    let generics = bounded_generics(
+        &generics,
+        fields
+            .iter()
+            .filter(|field| field.attributes.skip_deserializing)
+            .map(|field| field.ty),
+        parse_quote!(Default),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let container_name = name.to_string();
     let mut next_types = Vec::with_capacity(fields_count);
     let mut fields_initialization = Vec::with_capacity(fields_count);
-    let mut is_variable_sizes = Vec::with_capacity(fields_count);
-    let mut fixed_lengths = Vec::with_capacity(fields_count);
-    for field in fields {
-        let field_type = &field.ty;
-        let field_name = match &field.ident {
-            Some(ident) => ident,
-            _ => panic!("All fields must have names"),
-        };
-
-        next_types.push(quote! {
-            decoder.next_type::<#field_type>()?
-        });
-
-        fields_initialization.push(quote! {
-            #field_name: decoder.deserialize_next::<#field_type>()?
-        });
-
-        is_variable_sizes.push(quote! {
-            <#field_type as ssz::Deserialize>::is_variable_size()
-        });
+    for field in &fields {
+        let field_type = field.ty;
+        let field_name = field.name;
+        let field_name_str = field_name.to_string();
+
+        if field.attributes.skip_deserializing {
+            fields_initialization.push(quote! {
+                #field_name: <#field_type>::default()
+            });
+            continue;
+        }
 
-        fixed_lengths.push(quote! {
-           <#field_type>::fixed_length()
-        });
+        match (&field.attributes.with, &field.attributes.max_len) {
+            (Some(module), _) => {
+                next_types.push(quote! {
+                    decoder.next_type_with(#module::is_variable_size(), #module::fixed_length())?
+                });
+                fields_initialization.push(quote! {
+                    #field_name: decoder.deserialize_field_with(
+                        #container_name,
+                        #field_name_str,
+                        #module::is_variable_size(),
+                        #module::fixed_length(),
+                        #module::deserialize,
+                    )?
+                });
+            }
+            (None, Some(max_len)) => {
+                let item_type = vec_item_type(field_type).expect("checked when parsing attributes");
+                next_types.push(quote! {
+                    decoder.next_type::<#field_type>()?
+                });
+                fields_initialization.push(quote! {
+                    #field_name: decoder.deserialize_field_with(
+                        #container_name,
+                        #field_name_str,
+                        <#field_type as #krate::SszType>::is_variable_size(),
+                        <#field_type as #krate::SszType>::fixed_length(),
+                        |bytes| #krate::deserialize_with_max::<#item_type>(bytes, #max_len),
+                    )?
+                });
+            }
+            (None, None) => {
+                next_types.push(quote! {
+                    decoder.next_type::<#field_type>()?
+                });
+                fields_initialization.push(quote! {
+                    #field_name: decoder.deserialize_field::<#field_type>(
+                        #container_name,
+                        #field_name_str,
+                    )?
+                });
+            }
+        }
     }
 
-    let generated = quote! {
-        impl ssz::Deserialize for #name {
-            fn deserialize(bytes: &[u8]) -> Result<Self, ssz::Error> {
-                let mut decoder = ssz::Decoder::for_bytes(bytes);
+    Ok(quote! {
+        impl #impl_generics #krate::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(bytes: &[u8]) -> Result<Self, #krate::Error> {
+                let mut decoder = #krate::Decoder::for_bytes(bytes);
 
                 #(
                     #next_types;
@@ -168,22 +737,163 @@ pub fn deserialize_derive(input: TokenStream) -> TokenStream {
                     )*
                 })
             }
+        }
+    })
+}
 
-            fn is_variable_size() -> bool {
-                #(
-                    #is_variable_sizes ||
-                )*
-                    false
-            }
+fn expand_union_deserialize(
+    ast: &DeriveInput,
+    enum_data: &syn::DataEnum,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let variants = parse_variants(name, enum_data)?;
+
+    let generics = bounded_generics(
+        &ast.generics,
+        variants.iter().filter_map(|variant| variant.ty),
+        parse_quote!(#krate::Deserialize),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let arms = variants.iter().map(|variant| {
+        let variant_name = variant.name;
+        let selector = variant.selector;
+        match variant.ty {
+            Some(ty) => quote! {
+                (#selector, value_bytes) => {
+                    <#ty as #krate::Deserialize>::deserialize(value_bytes).map(#name::#variant_name)
+                }
+            },
+            None => quote! {
+                (#selector, []) => Ok(#name::#variant_name),
+                (#selector, _) => Err(#krate::Error::InvalidByteLength {
+                    got: bytes.len(),
+                    required: 1,
+                })
+            },
+        }
+    });
 
-            fn fixed_length() -> usize {
-                #(
-                    #fixed_lengths +
-                )*
-                    0
+    Ok(quote! {
+        impl #impl_generics #krate::Deserialize for #name #ty_generics #where_clause {
+            fn deserialize(bytes: &[u8]) -> Result<Self, #krate::Error> {
+                match #krate::deserialize_union_selector(bytes)? {
+                    #(
+                        #arms,
+                    )*
+                    (selector, _) => Err(#krate::Error::InvalidUnionSelector(selector)),
+                }
             }
         }
+    })
+}
+
+/// Generates `<Name>View<'ssz>`, a view over the encoded bytes of a struct with named fields that
+/// reads single fields without decoding the whole container.
+///
+/// `<Name>View::new(&bytes)` checks the offsets of the container and splits it into fields. Each
+/// field then has an accessor with the field's name and visibility returning an `ssz::View` of
+/// its bytes, which can be decoded on its own or, for lists and vectors, item by item. Views of
+/// nested containers are built from the bytes of their field, e.g.
+/// `ForkView::new(state.fork().as_bytes())`.
+///
+/// ## Field attributes
+///
+/// - `#[ssz(skip)]`: the field has no accessor.
+/// - `#[ssz(with = "module")]`: the accessor decodes the field with `module::deserialize(&[u8])`
+///   and returns `Result<T, ssz::Error>`.
+#[proc_macro_derive(SszView, attributes(ssz))]
+pub fn view_derive(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = syn::parse(input).expect("AST should be correct");
+
+    let generated = match &ast.data {
+        syn::Data::Struct(struct_data) => expand_struct_view(&ast, &struct_data.fields),
+        _ => Err(syn::Error::new_spanned(
+            &ast.ident,
+            "SszView only supports structs",
+        )),
     };
 
-    generated.into()
+    match generated {
+        Ok(generated) => generated.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn expand_struct_view(
+    ast: &DeriveInput,
+    fields: &syn::Fields,
+) -> Result<proc_macro2::TokenStream, syn::Error> {
+    let name = &ast.ident;
+    let vis = &ast.vis;
+    let view_name = syn::Ident::new(&format!("{}View", name), name.span());
+    let krate = ContainerAttributes::parse(ast)?.krate;
+    let fields = parse_fields(fields)?
+        .into_iter()
+        .filter(|field| !field.attributes.skip_deserializing)
+        .collect::<Vec<_>>();
+
+    let (_, container_ty_generics, _) = ast.generics.split_for_impl();
+    let mut view_generics = ast.generics.clone();
+    view_generics.params.insert(0, parse_quote!('ssz));
+    let (struct_generics, _, struct_where_clause) = view_generics.split_for_impl();
+
+    let bounded = bounded_generics(
+        &view_generics,
+        fields
+            .iter()
+            .filter(|field| field.attributes.with.is_none())
+            .map(|field| field.ty),
+        parse_quote!(#krate::SszType),
+    );
+    let (impl_generics, ty_generics, where_clause) = bounded.split_for_impl();
+
+    let layout = fields.iter().map(|field| {
+        let (is_variable_size, fixed_length) = field.layout(&krate);
+        quote! { (#is_variable_size, #fixed_length) }
+    });
+    let accessors = fields.iter().enumerate().map(|(index, field)| {
+        let field_name = field.name;
+        let field_vis = field.vis;
+        let field_type = field.ty;
+        match &field.attributes.with {
+            Some(module) => quote! {
+                #field_vis fn #field_name(&self) -> Result<#field_type, #krate::Error> {
+                    #module::deserialize(self.container.field(#index))
+                }
+            },
+            None => quote! {
+                #field_vis fn #field_name(&self) -> #krate::View<'ssz, #field_type> {
+                    #krate::View::new(self.container.field(#index))
+                }
+            },
+        }
+    });
+
+    Ok(quote! {
+        #vis struct #view_name #struct_generics #struct_where_clause {
+            container: #krate::ContainerView<'ssz>,
+            _container: std::marker::PhantomData<fn() -> #name #container_ty_generics>,
+        }
+
+        impl #impl_generics #view_name #ty_generics #where_clause {
+            pub fn new(bytes: &'ssz [u8]) -> Result<Self, #krate::Error> {
+                let layout: &[(bool, usize)] = &[#( #layout ),*];
+
+                Ok(Self {
+                    container: #krate::ContainerView::new(bytes, layout)?,
+                    _container: std::marker::PhantomData,
+                })
+            }
+
+            pub fn as_bytes(&self) -> &'ssz [u8] {
+                self.container.as_bytes()
+            }
+
+            #(
+                #accessors
+            )*
+        }
+    })
 }
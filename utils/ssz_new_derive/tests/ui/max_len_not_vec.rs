@@ -0,0 +1,9 @@
+use ssz_derive::SszType;
+
+#[derive(SszType)]
+struct Container {
+    #[ssz(max_len = "4")]
+    a: [u16; 4],
+}
+
+fn main() {}
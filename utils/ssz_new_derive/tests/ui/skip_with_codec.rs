@@ -0,0 +1,10 @@
+use ssz_derive::SszType;
+
+#[derive(SszType)]
+struct Container {
+    a: u16,
+    #[ssz(skip, with = "codec")]
+    b: u32,
+}
+
+fn main() {}
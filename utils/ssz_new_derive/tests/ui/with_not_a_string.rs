@@ -0,0 +1,9 @@
+use ssz_derive::SszType;
+
+#[derive(SszType)]
+struct Container {
+    #[ssz(with = codec)]
+    a: u16,
+}
+
+fn main() {}
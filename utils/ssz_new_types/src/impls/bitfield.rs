@@ -1,29 +1,80 @@
 use super::*;
+use crate::vendor::bytes_for_bit_len;
 use ssz::Error;
 
+impl<N: Unsigned + Clone> ssz::SszType for Bitfield<length::Variable<N>> {
+    fn is_variable_size() -> bool {
+        true
+    }
+
+    fn fixed_length() -> usize {
+        ssz::BYTES_PER_LENGTH_OFFSET
+    }
+}
+
 impl<N: Unsigned + Clone> ssz::Serialize for Bitfield<length::Variable<N>> {
-    fn serialize(&self) -> Result<Vec<u8>, ssz::Error> {
-        Ok(self.clone().into_bytes())
+    // Same bytes as `into_bytes`, without cloning `self`: the bits followed by a length bit.
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), ssz::Error> {
+        let start = buffer.len();
+        buffer.extend_from_slice(self.as_slice());
+        buffer.resize(start + self.serialized_len(), 0);
+        buffer[start + self.len() / 8] |= 1 << (self.len() % 8);
+        Ok(())
     }
 
-    fn is_variable_size() -> bool {
-        true
+    fn serialized_len(&self) -> usize {
+        bytes_for_bit_len(self.len() + 1)
     }
 }
 
 impl<N: Unsigned + Clone> ssz::Deserialize for Bitfield<length::Variable<N>> {
     fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
-        Self::from_bytes(bytes.to_vec()).map_err(|e| {
+        // The bits and the length bit take at most this many bytes, checked before copying them.
+        let max_bytes = bytes_for_bit_len(N::to_usize() + 1);
+        let bytes = ssz::deserialize_with_max::<u8>(bytes, max_bytes)?;
+
+        Self::from_bytes(bytes).map_err(|e| {
             ssz::Error::InvalidBytes(format!("Failed while creating BitList: {:?}", e))
         })
     }
+}
 
+impl<N: Unsigned + Clone> ssz::SszType for Bitfield<length::Fixed<N>> {
     fn is_variable_size() -> bool {
-        true
+        false
     }
 
     fn fixed_length() -> usize {
-        std::cmp::max(1, (N::to_usize() + 7) / 8)
+        bytes_for_bit_len(N::to_usize())
+    }
+}
+
+impl<N: Unsigned + Clone> ssz::Serialize for Bitfield<length::Fixed<N>> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), ssz::Error> {
+        buffer.extend_from_slice(self.as_slice());
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.as_slice().len()
+    }
+}
+
+impl<N: Unsigned + Clone> ssz::Deserialize for Bitfield<length::Fixed<N>> {
+    // `from_bytes` rejects bits set beyond `N` as well as the wrong number of bytes, but the
+    // length is checked first so it surfaces as an `InvalidByteLength`.
+    fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
+        let required = <Self as ssz::SszType>::fixed_length();
+        if bytes.len() != required {
+            return Err(Error::InvalidByteLength {
+                got: bytes.len(),
+                required,
+            });
+        }
+
+        Self::from_bytes(bytes.to_vec()).map_err(|e| {
+            ssz::Error::InvalidBytes(format!("Failed while creating BitVector: {:?}", e))
+        })
     }
 }
 
@@ -85,5 +136,110 @@ mod tests {
                 vec![0b0000_0000, 0b0000_0000, 0b0000_0001]
             );
         }
+
+        #[test]
+        fn serialize_into() {
+            let mut b = BitList16::with_capacity(11).unwrap();
+            b.set(0, true).unwrap();
+            b.set(10, true).unwrap();
+
+            let mut buffer = vec![7];
+            b.serialize_into(&mut buffer).unwrap();
+            assert_eq!(buffer, vec![7, 0b0000_0001, 0b0000_1100]);
+            assert_eq!(b.serialized_len(), 2);
+        }
+
+        #[test]
+        fn deserialize_too_many_bytes() {
+            use ssz::Deserialize;
+
+            assert_eq!(
+                BitList8::deserialize(&[0b0000_0000, 0b0000_0001]).unwrap(),
+                BitList8::with_capacity(8).unwrap()
+            );
+            // Rejected by length alone, whatever the bytes hold.
+            assert!(matches!(
+                BitList8::deserialize(&[0, 0, 0b0000_0001]),
+                Err(Error::TooMuchElements { got: 3, max: 2 })
+            ));
+        }
+    }
+
+    mod bitvector {
+        use super::*;
+        use ssz::Deserialize;
+
+        pub type BitVector4 = Bitfield<length::Fixed<U4>>;
+        pub type BitVector10 = Bitfield<length::Fixed<U10>>;
+        pub type BitVector16 = Bitfield<length::Fixed<U16>>;
+
+        #[test]
+        fn serialize() {
+            assert_eq!(BitVector4::new().serialize().unwrap(), vec![0b0000_0000]);
+
+            let mut b = BitVector16::new();
+            b.set(0, true).unwrap();
+            b.set(15, true).unwrap();
+            assert_eq!(b.serialize().unwrap(), vec![0b0000_0001, 0b1000_0000]);
+        }
+
+        #[test]
+        fn deserialize() {
+            let mut b = BitVector4::new();
+            b.set(3, true).unwrap();
+            assert_eq!(BitVector4::deserialize(&[0b0000_1000]).unwrap(), b);
+            assert_eq!(<BitVector16 as ssz::SszType>::fixed_length(), 2);
+            assert!(BitVector16::deserialize(&[0]).is_err());
+        }
+
+        #[test]
+        fn deserialize_all_bits() {
+            let mut b = BitVector10::new();
+            for i in 0..10 {
+                b.set(i, true).unwrap();
+            }
+            assert_eq!(
+                BitVector10::deserialize(&[0b1111_1111, 0b0000_0011]).unwrap(),
+                b
+            );
+            assert_eq!(
+                BitVector16::deserialize(&[255, 255])
+                    .unwrap()
+                    .num_set_bits(),
+                16
+            );
+        }
+
+        #[test]
+        fn deserialize_excess_bits() {
+            assert!(BitVector4::deserialize(&[0b0001_0000]).is_err());
+            assert!(BitVector4::deserialize(&[0b1000_0000]).is_err());
+            assert!(BitVector10::deserialize(&[0, 0b0000_0100]).is_err());
+        }
+
+        #[test]
+        fn deserialize_wrong_length() {
+            match BitVector10::deserialize(&[0, 0, 0]) {
+                Err(Error::InvalidByteLength { got, required }) => {
+                    assert_eq!((got, required), (3, 2))
+                }
+                result => panic!("unexpected result: {:?}", result),
+            }
+            assert!(BitVector4::deserialize(&[]).is_err());
+        }
+
+        #[test]
+        fn tree_hash_root() {
+            use tree_hash::TreeHash;
+
+            let mut b = BitVector10::new();
+            b.set(0, true).unwrap();
+            b.set(9, true).unwrap();
+
+            let mut chunk = vec![0; 32];
+            chunk[..2].copy_from_slice(&[0b0000_0001, 0b0000_0010]);
+            assert_eq!(b.tree_hash_root(), chunk);
+            assert_eq!(BitVector4::new().tree_hash_root(), vec![0; 32]);
+        }
     }
 }
@@ -1,106 +1,71 @@
 use super::*;
 
-impl<T: ssz::Serialize, N: Unsigned> ssz::Serialize for FixedVector<T, N> {
-    fn serialize(&self) -> Result<Vec<u8>, ssz::Error> {
-        if T::is_variable_size() {
-            let mut variable_parts = Vec::with_capacity(self.len());
-            for element in self.iter() {
-                variable_parts.push(element.serialize()?)
-            }
-
-            let fixed_length = self.len() * ssz::BYTES_PER_LENGTH_OFFSET;
-            let variable_lengths: Vec<usize> =
-                variable_parts.iter().map(|part| part.len()).collect();
-
-            let mut variable_offsets = Vec::with_capacity(self.len());
-            for i in 0..self.len() {
-                let variable_length_sum: usize = variable_lengths[..i].iter().sum();
-                let offset = fixed_length + variable_length_sum;
-                variable_offsets.push(ssz::serialize_offset(offset)?);
-            }
-
-            let variable_len: usize = variable_lengths.iter().sum();
-            let total_len = fixed_length + variable_len;
-            let mut result = Vec::with_capacity(total_len);
-            for offset in variable_offsets {
-                result.extend(offset);
-            }
-
-            for part in variable_parts {
-                result.extend(part);
-            }
+impl<T: ssz::SszType, N: Unsigned> ssz::SszType for FixedVector<T, N> {
+    fn is_variable_size() -> bool {
+        T::is_variable_size()
+    }
 
-            Ok(result)
+    fn fixed_length() -> usize {
+        if T::is_variable_size() {
+            ssz::BYTES_PER_LENGTH_OFFSET
         } else {
-            let mut fixed_parts = Vec::with_capacity(self.len());
-            for element in self.iter() {
-                fixed_parts.push(element.serialize()?);
-            }
-
-            let len = fixed_parts.iter().map(|part| part.len()).sum();
-
-            let mut result = Vec::with_capacity(len);
-            for part in fixed_parts {
-                result.extend(part);
-            }
-
-            Ok(result)
+            N::to_usize() * T::fixed_length()
         }
     }
+}
 
-    fn is_variable_size() -> bool {
-        <T as ssz::Serialize>::is_variable_size()
+impl<T: ssz::Serialize, N: Unsigned> ssz::Serialize for FixedVector<T, N> {
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), ssz::Error> {
+        ssz::serialize_items_into(self, buffer)
+    }
+
+    fn serialized_len(&self) -> usize {
+        ssz::serialized_items_len(self)
     }
 }
 
 impl<T: ssz::Deserialize + Default, N: Unsigned> ssz::Deserialize for FixedVector<T, N> {
     fn deserialize(bytes: &[u8]) -> Result<Self, ssz::Error> {
-        if bytes.is_empty() {
-            return Err(ssz::Error::InvalidByteLength {
-                got: 0,
-                required: T::fixed_length(),
-            });
-        }
-
         let items_count = N::to_usize();
-        if <T as ssz::Deserialize>::is_variable_size() {
-            let items = ssz::deserialize_variable_sized_items(bytes)?;
+        if T::is_variable_size() {
+            let items = ssz::deserialize_with_max(bytes, items_count)?;
 
             if items_count == items.len() {
                 Ok(items.into())
             } else {
                 Err(ssz::Error::InvalidBytes(format!(
-                    "Cannot parse FixedVector[{}] from bytes",
-                    items_count
+                    "Cannot parse a vector of {} items from {} items",
+                    items_count,
+                    items.len()
                 )))
             }
         } else {
-            if bytes.len() % items_count == 0 {
-                let mut result = Vec::with_capacity(items_count);
-                for chunk in bytes.chunks(T::fixed_length()) {
-                    result.push(T::deserialize(chunk)?);
-                }
-
-                Ok(result.into())
-            } else {
-                Err(ssz::Error::InvalidByteLength {
+            let item_length = T::fixed_length();
+            let required = items_count * item_length;
+            if bytes.len() != required {
+                return Err(ssz::Error::InvalidByteLength {
                     got: bytes.len(),
-                    required: bytes.len() / T::fixed_length() + 1,
-                })
+                    required,
+                });
             }
+
+            (0..items_count)
+                .map(|index| {
+                    let start = index * item_length;
+                    T::deserialize(&bytes[start..][..item_length])
+                        .map_err(|e| e.in_item(index, start))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Into::into)
         }
     }
+}
 
-    fn is_variable_size() -> bool {
-        <T as ssz::Deserialize>::is_variable_size()
-    }
+impl<T: ssz::Deserialize + Default, N: Unsigned> ssz::Sequence for FixedVector<T, N> {
+    type Item = T;
 
-    fn fixed_length() -> usize {
-        if <T as ssz::Deserialize>::is_variable_size() {
-            ssz::BYTES_PER_LENGTH_OFFSET
-        } else {
-            N::to_usize() * <T as ssz::Deserialize>::fixed_length()
-        }
+    fn fixed_len() -> Option<usize> {
+        Some(N::to_usize())
     }
 }
 
@@ -109,6 +74,23 @@ mod test {
     use super::*;
     use ssz::Serialize;
 
+    #[test]
+    fn fixed_length() {
+        use ssz::SszType;
+
+        assert!(!<FixedVector<u16, typenum::U3>>::is_variable_size());
+        assert_eq!(<FixedVector<u16, typenum::U3>>::fixed_length(), 6);
+        assert!(<FixedVector<Vec<u8>, typenum::U3>>::is_variable_size());
+        assert_eq!(
+            <FixedVector<Vec<u8>, typenum::U3>>::fixed_length(),
+            ssz::BYTES_PER_LENGTH_OFFSET
+        );
+        assert_eq!(
+            <FixedVector<FixedVector<u8, typenum::U2>, typenum::U3>>::fixed_length(),
+            6
+        );
+    }
+
     mod serialize {
         use super::*;
 
@@ -182,11 +164,41 @@ mod test {
                 let result = <FixedVector<u8, U6> as Deserialize>::deserialize(&[1, 2, 3, 4]);
                 assert!(result.is_err());
 
+                // a whole number of items, but not `N` of them
+                let result = <FixedVector<u16, U3> as Deserialize>::deserialize(&[1, 0, 2, 0]);
+                assert!(result.is_err());
+                let result = <FixedVector<u16, U5> as Deserialize>::deserialize(&[0; 20]);
+                assert!(result.is_err());
+
                 let result = <FixedVector<Vec<u8>, U6> as Deserialize>::deserialize(&[
                     12, 0, 0, 0, 14, 0, 0, 0, 14, 0, 0, 0, 1, 2, 3,
                 ]);
                 assert!(result.is_err());
             }
+
+            #[test]
+            fn item_path() {
+                let result = <FixedVector<bool, U3> as Deserialize>::deserialize(&[1, 0, 2]);
+                assert_eq!(
+                    result.unwrap_err().to_string(),
+                    "[2] (at byte 2): invalid bytes: Cannot deserialize bool from 2"
+                );
+            }
+
+            #[test]
+            fn view_wrong_count() {
+                let bytes = [12, 0, 0, 0, 14, 0, 0, 0, 14, 0, 0, 0, 1, 2, 3];
+                let view = ssz::View::<FixedVector<Vec<u8>, U5>>::new(&bytes);
+                assert_eq!(
+                    view.len().unwrap_err().to_string(),
+                    <FixedVector<Vec<u8>, U5>>::deserialize(&bytes)
+                        .unwrap_err()
+                        .to_string()
+                );
+                assert!(ssz::View::<FixedVector<u16, U3>>::new(&[1, 0, 2, 0])
+                    .len()
+                    .is_err());
+            }
         }
     }
 }
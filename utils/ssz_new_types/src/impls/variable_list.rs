@@ -1,38 +1,41 @@
 use super::*;
 use ssz::{Deserialize, Error, Serialize};
 
+impl<T, N: Unsigned> ssz::SszType for VariableList<T, N> {
+    fn is_variable_size() -> bool {
+        <Vec<T>>::is_variable_size()
+    }
+
+    fn fixed_length() -> usize {
+        <Vec<T>>::fixed_length()
+    }
+}
+
 impl<T: Serialize + Clone, N: Unsigned> ssz::Serialize for VariableList<T, N> {
-    fn serialize(&self) -> Result<Vec<u8>, Error> {
-        self.vec.serialize()
+    fn serialize_into(&self, buffer: &mut Vec<u8>) -> Result<(), Error> {
+        self.vec.serialize_into(buffer)
     }
 
-    fn is_variable_size() -> bool {
-        <Vec<T>>::is_variable_size()
+    fn serialized_len(&self) -> usize {
+        self.vec.serialized_len()
     }
 }
 
 impl<T: Deserialize, N: Unsigned> ssz::Deserialize for VariableList<T, N> {
     fn deserialize(bytes: &[u8]) -> Result<Self, Error> {
-        let items = <Vec<T>>::deserialize(bytes)?;
-
-        if items.len() <= N::to_usize() {
-            Self::new(items).map_err(|e| {
-                ssz::Error::InvalidBytes(format!("Failed while creating VariableList: {:?}", e))
-            })
-        } else {
-            Err(ssz::Error::TooMuchElements {
-                got: items.len(),
-                max: N::to_usize(),
-            })
-        }
-    }
+        let items = ssz::deserialize_with_max(bytes, N::to_usize())?;
 
-    fn is_variable_size() -> bool {
-        <Vec<T>>::is_variable_size()
+        Self::new(items).map_err(|e| {
+            ssz::Error::InvalidBytes(format!("Failed while creating VariableList: {:?}", e))
+        })
     }
+}
 
-    fn fixed_length() -> usize {
-        <Vec<T>>::fixed_length()
+impl<T: Deserialize, N: Unsigned> ssz::Sequence for VariableList<T, N> {
+    type Item = T;
+
+    fn max_len() -> Option<usize> {
+        Some(N::to_usize())
     }
 }
 
@@ -50,4 +53,26 @@ mod tests {
             assert_eq!(vec.serialize().unwrap(), vec![1, 0, 2, 0, 3, 0, 4, 0]);
         }
     }
+
+    #[test]
+    fn read_items_over_max_len() {
+        let bytes = vec![1u16, 2, 3].serialize().unwrap();
+        let items = ssz::ItemsReader::<_, VariableList<u16, U2>>::new(&bytes[..])
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(
+            items,
+            Err(Error::TooMuchElements { got: 3, max: 2 })
+        ));
+    }
+
+    #[test]
+    fn view_over_max_len() {
+        let bytes = vec![1u16, 2, 3].serialize().unwrap();
+        let view = ssz::View::<VariableList<u16, U2>>::new(&bytes);
+        assert!(matches!(
+            view.len(),
+            Err(Error::TooMuchElements { got: 3, max: 2 })
+        ));
+        assert!(view.get(0).is_err());
+    }
 }
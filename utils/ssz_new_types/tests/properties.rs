@@ -0,0 +1,194 @@
+//! Round trips randomly generated containers that nest the `ssz_types` collections in each other,
+//! checking that serialization and deserialization agree on their layout.
+
+use std::fmt::Debug;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ssz::{Deserialize, Serialize, SszDeserialize, SszSerialize, SszType};
+use ssz_types::typenum::{U10, U16, U2, U20, U3, U4};
+use ssz_types::{BitList, BitVector, FixedVector, VariableList};
+
+const CASES: usize = 200;
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug, Clone, Default)]
+struct Fixed {
+    a: u8,
+    bits: BitVector<U10>,
+    numbers: FixedVector<u16, U3>,
+    flag: bool,
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug, Clone)]
+struct Variable {
+    a: u16,
+    bytes: VariableList<u8, U16>,
+    bits: BitList<U20>,
+    option: Option<u32>,
+}
+
+// `FixedVector` pads with default items, so its items must have a default.
+impl Default for Variable {
+    fn default() -> Self {
+        Self {
+            a: 0,
+            bytes: VariableList::default(),
+            bits: BitList::with_capacity(0).expect("empty bitlist"),
+            option: None,
+        }
+    }
+}
+
+#[derive(SszType, SszSerialize, SszDeserialize, PartialEq, Debug, Clone)]
+struct Outer {
+    fixed: Fixed,
+    variable: Variable,
+    fixed_vector: FixedVector<Fixed, U2>,
+    variable_vector: FixedVector<Variable, U2>,
+    lists: VariableList<Vec<u8>, U4>,
+    fixed_items: Vec<Fixed>,
+    tail: u64,
+}
+
+fn random_bytes(rng: &mut StdRng, max_len: usize) -> Vec<u8> {
+    let len = rng.gen_range(0, max_len + 1);
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+fn random_fixed(rng: &mut StdRng) -> Fixed {
+    let mut bits = BitVector::new();
+    for index in 0..bits.len() {
+        bits.set(index, rng.gen()).expect("index is in range");
+    }
+
+    Fixed {
+        a: rng.gen(),
+        bits,
+        numbers: FixedVector::new(vec![rng.gen(), rng.gen(), rng.gen()]).expect("3 numbers"),
+        flag: rng.gen(),
+    }
+}
+
+fn random_variable(rng: &mut StdRng) -> Variable {
+    let mut bits = BitList::with_capacity(rng.gen_range(0, 21)).expect("capacity is in range");
+    for index in 0..bits.len() {
+        bits.set(index, rng.gen()).expect("index is in range");
+    }
+
+    Variable {
+        a: rng.gen(),
+        bytes: VariableList::new(random_bytes(rng, 16)).expect("at most 16 bytes"),
+        bits,
+        option: if rng.gen() { Some(rng.gen()) } else { None },
+    }
+}
+
+fn random_outer(rng: &mut StdRng) -> Outer {
+    let lists = (0..rng.gen_range(0, 5))
+        .map(|_| random_bytes(rng, 8))
+        .collect();
+    let fixed_items = (0..rng.gen_range(0, 4))
+        .map(|_| random_fixed(rng))
+        .collect();
+
+    Outer {
+        fixed: random_fixed(rng),
+        variable: random_variable(rng),
+        fixed_vector: FixedVector::new(vec![random_fixed(rng), random_fixed(rng)])
+            .expect("2 items"),
+        variable_vector: FixedVector::new(vec![random_variable(rng), random_variable(rng)])
+            .expect("2 items"),
+        lists: VariableList::new(lists).expect("at most 4 lists"),
+        fixed_items,
+        tail: rng.gen(),
+    }
+}
+
+fn check_round_trip<T>(value: &T)
+where
+    T: Serialize + Deserialize + PartialEq + Debug,
+{
+    let bytes = value.serialize().expect("value should serialize");
+    assert_eq!(value.serialized_len(), bytes.len(), "{:?}", value);
+    assert_eq!(
+        &T::deserialize(&bytes).expect("bytes should deserialize"),
+        value
+    );
+
+    if !T::is_variable_size() {
+        assert_eq!(bytes.len(), T::fixed_length(), "{:?}", value);
+        assert!(T::deserialize(&bytes[1..]).is_err(), "{:?}", value);
+
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(T::deserialize(&longer).is_err(), "{:?}", value);
+    }
+}
+
+fn run<T>(seed: u64, random: impl Fn(&mut StdRng) -> T)
+where
+    T: Serialize + Deserialize + PartialEq + Debug,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    for _ in 0..CASES {
+        check_round_trip(&random(&mut rng));
+    }
+}
+
+#[test]
+fn layouts() {
+    assert!(!Fixed::is_variable_size());
+    assert_eq!(Fixed::fixed_length(), 1 + 2 + 3 * 2 + 1);
+    assert!(Variable::is_variable_size());
+    assert!(Outer::is_variable_size());
+    assert_eq!(Outer::fixed_length(), ssz::BYTES_PER_LENGTH_OFFSET);
+    assert_eq!(
+        <FixedVector<Fixed, U2>>::fixed_length(),
+        2 * Fixed::fixed_length()
+    );
+    assert_eq!(
+        <FixedVector<Variable, U2>>::fixed_length(),
+        ssz::BYTES_PER_LENGTH_OFFSET
+    );
+}
+
+#[test]
+fn first_offset_follows_fixed_part() {
+    let value = random_outer(&mut StdRng::seed_from_u64(0));
+    let bytes = value.serialize().expect("value should serialize");
+
+    // `variable` is the first variable size field, and its offset points right past the fixed
+    // part: `fixed`, four offsets, `fixed_vector` and `tail`.
+    let fixed_part_len = 3 * Fixed::fixed_length() + 4 * ssz::BYTES_PER_LENGTH_OFFSET + 8;
+    let offset_position = Fixed::fixed_length();
+    let offset = ssz::deserialize_offset(
+        &bytes[offset_position..offset_position + ssz::BYTES_PER_LENGTH_OFFSET],
+    )
+    .expect("offset should deserialize");
+
+    assert_eq!(offset, fixed_part_len);
+}
+
+#[test]
+fn fixed_round_trips() {
+    run(1, random_fixed);
+}
+
+#[test]
+fn variable_round_trips() {
+    run(2, random_variable);
+}
+
+#[test]
+fn outer_round_trips() {
+    run(3, random_outer);
+}
+
+#[test]
+fn lists_of_outer_round_trip() {
+    run(4, |rng| {
+        (0..rng.gen_range(0, 4))
+            .map(|_| random_outer(rng))
+            .collect::<Vec<_>>()
+    });
+}
@@ -3,19 +3,20 @@ extern crate lazy_static;
 
 use criterion::Criterion;
 use criterion::{black_box, criterion_group, criterion_main, Benchmark};
+use tree_hash::TreeHash;
+use types::beacon_state::BeaconState;
+use types::config::{Config, MainnetConfig, MinimalConfig};
+use types::primitives::Keypair;
 use types::test_utils::{generate_deterministic_keypairs, TestingBeaconStateBuilder};
-use types::{BeaconState, EthSpec, Keypair, MainnetEthSpec, MinimalEthSpec};
 
 lazy_static! {
-    static ref KEYPAIRS: Vec<Keypair> = { generate_deterministic_keypairs(300_000) };
+    static ref KEYPAIRS: Vec<Keypair> = generate_deterministic_keypairs(300_000);
 }
 
-fn build_state<T: EthSpec>(validator_count: usize) -> BeaconState<T> {
-    let (state, _keypairs) = TestingBeaconStateBuilder::from_keypairs(
-        KEYPAIRS[0..validator_count].to_vec(),
-        &T::default_spec(),
-    )
-    .build();
+fn build_state<C: Config>(validator_count: usize) -> BeaconState<C> {
+    let (state, _keypairs) =
+        TestingBeaconStateBuilder::<C>::from_keypairs(KEYPAIRS[0..validator_count].to_vec())
+            .build();
 
     assert_eq!(state.validators.len(), validator_count);
     assert_eq!(state.balances.len(), validator_count);
@@ -27,20 +28,20 @@ fn build_state<T: EthSpec>(validator_count: usize) -> BeaconState<T> {
     state
 }
 
-fn bench_suite<T: EthSpec>(c: &mut Criterion, spec_desc: &str, validator_count: usize) {
-    let state = build_state::<T>(validator_count);
+fn bench_suite<C: Config + 'static>(c: &mut Criterion, spec_desc: &str, validator_count: usize) {
+    let state = build_state::<C>(validator_count);
 
     c.bench(
         &format!("{}/{}_validators", spec_desc, validator_count),
         Benchmark::new("genesis_state", move |b| {
             b.iter_batched_ref(
                 || state.clone(),
-                // Note: `state.canonical_root()` uses whatever `tree_hash` that the `types` crate
+                // Note: `state.tree_hash_root()` uses whatever `tree_hash` that the `types` crate
                 // uses, which is not necessarily this crate. If you want to ensure that types is
                 // using this local version of `tree_hash`, ensure you add a workspace-level
                 // [dependency
                 // patch](https://doc.rust-lang.org/cargo/reference/manifest.html#the-patch-section).
-                |state| black_box(state.canonical_root()),
+                |state| black_box(state.tree_hash_root()),
                 criterion::BatchSize::SmallInput,
             )
         })
@@ -49,11 +50,11 @@ fn bench_suite<T: EthSpec>(c: &mut Criterion, spec_desc: &str, validator_count:
 }
 
 fn all_benches(c: &mut Criterion) {
-    bench_suite::<MinimalEthSpec>(c, "minimal", 100_000);
-    bench_suite::<MinimalEthSpec>(c, "minimal", 300_000);
+    bench_suite::<MinimalConfig>(c, "minimal", 100_000);
+    bench_suite::<MinimalConfig>(c, "minimal", 300_000);
 
-    bench_suite::<MainnetEthSpec>(c, "mainnet", 100_000);
-    bench_suite::<MainnetEthSpec>(c, "mainnet", 300_000);
+    bench_suite::<MainnetConfig>(c, "mainnet", 100_000);
+    bench_suite::<MainnetConfig>(c, "mainnet", 300_000);
 }
 
 criterion_group!(benches, all_benches,);
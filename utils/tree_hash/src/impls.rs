@@ -131,6 +131,28 @@ impl TreeHash for H256 {
     }
 }
 
+/// The SSZ `Union[None, T]` type, where `None` has a zero root.
+impl<T: TreeHash> TreeHash for Option<T> {
+    fn tree_hash_type() -> TreeHashType {
+        TreeHashType::Container
+    }
+
+    fn tree_hash_packed_encoding(&self) -> Vec<u8> {
+        unreachable!("Union should never be packed.")
+    }
+
+    fn tree_hash_packing_factor() -> usize {
+        unreachable!("Union should never be packed.")
+    }
+
+    fn tree_hash_root(&self) -> Vec<u8> {
+        match self {
+            None => mix_in_selector(&[0; BYTES_PER_CHUNK], 0),
+            Some(value) => mix_in_selector(&value.tree_hash_root(), 1),
+        }
+    }
+}
+
 // TODO: this implementation always panics, it only exists to allow us to compile whilst
 // refactoring tree hash. Should be removed.
 macro_rules! impl_for_list {
@@ -183,6 +205,18 @@ mod test {
         assert_eq!(false.tree_hash_root(), false_bytes);
     }
 
+    #[test]
+    fn option() {
+        assert_eq!(
+            None::<u64>.tree_hash_root(),
+            mix_in_selector(&[0; BYTES_PER_CHUNK], 0)
+        );
+        assert_eq!(
+            Some(5_u64).tree_hash_root(),
+            mix_in_selector(&5_u64.tree_hash_root(), 1)
+        );
+    }
+
     #[test]
     fn int_to_bytes() {
         assert_eq!(&int_to_bytes32(0), &[0; 32]);
@@ -39,6 +39,16 @@ pub fn mix_in_length(root: &[u8], length: usize) -> Vec<u8> {
     merkleize_padded::hash_concat(root, &length_bytes)
 }
 
+/// Returns the node created by hashing `root` and `selector`.
+///
+/// Used in `TreeHash` for inserting the selector of a union above the root of its value.
+pub fn mix_in_selector(root: &[u8], selector: u8) -> Vec<u8> {
+    let mut selector_bytes = vec![selector];
+    selector_bytes.resize(BYTES_PER_CHUNK, 0);
+
+    merkleize_padded::hash_concat(root, &selector_bytes)
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum TreeHashType {
     Basic,
@@ -122,4 +132,16 @@ mod test {
 
         assert_eq!(mix_in_length(&[42; BYTES_PER_CHUNK], 42), hash);
     }
+
+    #[test]
+    fn mix_selector() {
+        let hash = {
+            let mut preimage = vec![42; BYTES_PER_CHUNK];
+            preimage.append(&mut vec![7]);
+            preimage.append(&mut vec![0; BYTES_PER_CHUNK - 1]);
+            eth2_hashing::hash(&preimage)
+        };
+
+        assert_eq!(mix_in_selector(&[42; BYTES_PER_CHUNK], 7), hash);
+    }
 }
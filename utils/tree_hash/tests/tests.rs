@@ -0,0 +1,52 @@
+use tree_hash::{mix_in_selector, TreeHash, BYTES_PER_CHUNK};
+use tree_hash_derive::TreeHash;
+
+#[derive(TreeHash)]
+struct Fixed {
+    a: u16,
+    b: bool,
+}
+
+#[derive(TreeHash)]
+enum Union {
+    None,
+    Number(u16),
+    Fixed(Fixed),
+}
+
+#[test]
+fn union_mixes_in_selector() {
+    let fixed = Fixed { a: 5, b: true };
+    let fixed_root = fixed.tree_hash_root();
+
+    assert_eq!(
+        Union::None.tree_hash_root(),
+        mix_in_selector(&[0; BYTES_PER_CHUNK], 0)
+    );
+    assert_eq!(
+        Union::Number(7).tree_hash_root(),
+        mix_in_selector(&7_u16.tree_hash_root(), 1)
+    );
+    assert_eq!(
+        Union::Fixed(fixed).tree_hash_root(),
+        mix_in_selector(&fixed_root, 2)
+    );
+}
+
+#[test]
+fn union_matches_option() {
+    #[derive(TreeHash)]
+    enum Optional {
+        None,
+        Some(u64),
+    }
+
+    assert_eq!(
+        Optional::None.tree_hash_root(),
+        None::<u64>.tree_hash_root()
+    );
+    assert_eq!(
+        Optional::Some(3).tree_hash_root(),
+        Some(3_u64).tree_hash_root()
+    );
+}
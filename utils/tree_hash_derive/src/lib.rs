@@ -41,6 +41,9 @@ fn should_skip_hashing(field: &syn::Field) -> bool {
 /// Implements `tree_hash::TreeHash` for some `struct`.
 ///
 /// Fields are hashed in the order they are defined.
+///
+/// Enums are hashed as SSZ unions: the root of the value a variant holds, or a zero chunk for an
+/// empty variant, is mixed in with the position of the variant.
 #[proc_macro_derive(TreeHash, attributes(tree_hash))]
 pub fn tree_hash_derive(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as DeriveInput);
@@ -50,7 +53,8 @@ pub fn tree_hash_derive(input: TokenStream) -> TokenStream {
 
     let struct_data = match &item.data {
         syn::Data::Struct(s) => s,
-        _ => panic!("tree_hash_derive only supports structs."),
+        syn::Data::Enum(e) => return tree_hash_union_derive(&item, e),
+        _ => panic!("tree_hash_derive only supports structs and enums."),
     };
 
     let idents = get_hashable_named_field_idents(&struct_data);
@@ -83,6 +87,101 @@ pub fn tree_hash_derive(input: TokenStream) -> TokenStream {
     output.into()
 }
 
+/// Selectors are the variants' positions, so unions can have at most 128 variants.
+const MAX_UNION_VARIANTS: usize = 128;
+
+/// Checks that `enum_data` describes an SSZ union: at most 128 variants, each holding a single
+/// unnamed value, except the first which may be empty.
+fn check_union_variants(name: &syn::Ident, enum_data: &syn::DataEnum) -> Result<(), syn::Error> {
+    if enum_data.variants.len() > MAX_UNION_VARIANTS {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("a union can have at most {} variants", MAX_UNION_VARIANTS),
+        ));
+    }
+
+    for (index, variant) in enum_data.variants.iter().enumerate() {
+        if let Some((_, discriminant)) = &variant.discriminant {
+            return Err(syn::Error::new_spanned(
+                discriminant,
+                "the union selector is the variant's position and cannot be set explicitly",
+            ));
+        }
+        match &variant.fields {
+            syn::Fields::Unit if index == 0 => {}
+            syn::Fields::Unit => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "only the first variant of a union can be empty",
+                ))
+            }
+            syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {}
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "union variants must hold a single unnamed value",
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn tree_hash_union_derive(item: &DeriveInput, enum_data: &syn::DataEnum) -> TokenStream {
+    let name = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = &item.generics.split_for_impl();
+
+    if let Err(error) = check_union_variants(name, enum_data) {
+        return error.to_compile_error().into();
+    }
+
+    let arms = enum_data
+        .variants
+        .iter()
+        .enumerate()
+        .map(|(index, variant)| {
+            let variant_name = &variant.ident;
+            let selector = index as u8;
+            match &variant.fields {
+                syn::Fields::Unit => quote! {
+                    #name::#variant_name => {
+                        tree_hash::mix_in_selector(&[0; tree_hash::BYTES_PER_CHUNK], #selector)
+                    }
+                },
+                _ => quote! {
+                    #name::#variant_name(value) => {
+                        tree_hash::mix_in_selector(&value.tree_hash_root(), #selector)
+                    }
+                },
+            }
+        });
+
+    let output = quote! {
+        impl #impl_generics tree_hash::TreeHash for #name #ty_generics #where_clause {
+            fn tree_hash_type() -> tree_hash::TreeHashType {
+                tree_hash::TreeHashType::Container
+            }
+
+            fn tree_hash_packed_encoding(&self) -> Vec<u8> {
+                unreachable!("Union should never be packed.")
+            }
+
+            fn tree_hash_packing_factor() -> usize {
+                unreachable!("Union should never be packed.")
+            }
+
+            fn tree_hash_root(&self) -> Vec<u8> {
+                match self {
+                    #(
+                        #arms
+                    )*
+                }
+            }
+        }
+    };
+    output.into()
+}
+
 #[proc_macro_derive(SignedRoot, attributes(signed_root))]
 pub fn tree_hash_signed_root_derive(input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as DeriveInput);
@@ -0,0 +1,7 @@
+// Checks the errors the derives report for misused attributes, against the `.stderr` snapshots
+// next to each case. Run with `TRYBUILD=overwrite` to update them.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
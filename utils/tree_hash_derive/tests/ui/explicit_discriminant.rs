@@ -0,0 +1,8 @@
+use tree_hash_derive::TreeHash;
+
+#[derive(TreeHash)]
+enum Union {
+    A = 1,
+}
+
+fn main() {}
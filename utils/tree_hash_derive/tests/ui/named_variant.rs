@@ -0,0 +1,9 @@
+use tree_hash_derive::TreeHash;
+
+#[derive(TreeHash)]
+enum Union {
+    A(u8),
+    B { value: u16 },
+}
+
+fn main() {}
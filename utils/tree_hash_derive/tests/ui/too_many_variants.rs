@@ -0,0 +1,136 @@
+use tree_hash_derive::TreeHash;
+
+#[derive(TreeHash)]
+enum Union {
+    V0(u8),
+    V1(u8),
+    V2(u8),
+    V3(u8),
+    V4(u8),
+    V5(u8),
+    V6(u8),
+    V7(u8),
+    V8(u8),
+    V9(u8),
+    V10(u8),
+    V11(u8),
+    V12(u8),
+    V13(u8),
+    V14(u8),
+    V15(u8),
+    V16(u8),
+    V17(u8),
+    V18(u8),
+    V19(u8),
+    V20(u8),
+    V21(u8),
+    V22(u8),
+    V23(u8),
+    V24(u8),
+    V25(u8),
+    V26(u8),
+    V27(u8),
+    V28(u8),
+    V29(u8),
+    V30(u8),
+    V31(u8),
+    V32(u8),
+    V33(u8),
+    V34(u8),
+    V35(u8),
+    V36(u8),
+    V37(u8),
+    V38(u8),
+    V39(u8),
+    V40(u8),
+    V41(u8),
+    V42(u8),
+    V43(u8),
+    V44(u8),
+    V45(u8),
+    V46(u8),
+    V47(u8),
+    V48(u8),
+    V49(u8),
+    V50(u8),
+    V51(u8),
+    V52(u8),
+    V53(u8),
+    V54(u8),
+    V55(u8),
+    V56(u8),
+    V57(u8),
+    V58(u8),
+    V59(u8),
+    V60(u8),
+    V61(u8),
+    V62(u8),
+    V63(u8),
+    V64(u8),
+    V65(u8),
+    V66(u8),
+    V67(u8),
+    V68(u8),
+    V69(u8),
+    V70(u8),
+    V71(u8),
+    V72(u8),
+    V73(u8),
+    V74(u8),
+    V75(u8),
+    V76(u8),
+    V77(u8),
+    V78(u8),
+    V79(u8),
+    V80(u8),
+    V81(u8),
+    V82(u8),
+    V83(u8),
+    V84(u8),
+    V85(u8),
+    V86(u8),
+    V87(u8),
+    V88(u8),
+    V89(u8),
+    V90(u8),
+    V91(u8),
+    V92(u8),
+    V93(u8),
+    V94(u8),
+    V95(u8),
+    V96(u8),
+    V97(u8),
+    V98(u8),
+    V99(u8),
+    V100(u8),
+    V101(u8),
+    V102(u8),
+    V103(u8),
+    V104(u8),
+    V105(u8),
+    V106(u8),
+    V107(u8),
+    V108(u8),
+    V109(u8),
+    V110(u8),
+    V111(u8),
+    V112(u8),
+    V113(u8),
+    V114(u8),
+    V115(u8),
+    V116(u8),
+    V117(u8),
+    V118(u8),
+    V119(u8),
+    V120(u8),
+    V121(u8),
+    V122(u8),
+    V123(u8),
+    V124(u8),
+    V125(u8),
+    V126(u8),
+    V127(u8),
+    V128(u8),
+}
+
+fn main() {}